@@ -0,0 +1,47 @@
+//! The walls the balls bounce off.
+
+use amethyst::window::ScreenDimensions;
+use serde::{Deserialize, Serialize};
+
+/// The walls the balls bounce off.
+///
+/// Unless the scenario gives bounds of its own, the arena matches the window and follows it as it
+/// is resized.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ArenaBounds {
+    /// Left wall.
+    pub min_x: f32,
+    /// Bottom wall.
+    pub min_y: f32,
+    /// Right wall.
+    pub max_x: f32,
+    /// Top wall.
+    pub max_y: f32,
+}
+
+impl ArenaBounds {
+    /// An arena with its corner at the origin.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: width,
+            max_y: height,
+        }
+    }
+
+    /// An arena the size of the window.
+    pub fn from_screen(screen: &ScreenDimensions) -> Self {
+        Self::new(screen.width(), screen.height())
+    }
+
+    /// Distance between the left and right walls.
+    pub fn width(&self) -> f32 {
+        self.max_x - self.min_x
+    }
+
+    /// Distance between the bottom and top walls.
+    pub fn height(&self) -> f32 {
+        self.max_y - self.min_y
+    }
+}
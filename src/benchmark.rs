@@ -0,0 +1,267 @@
+//! Frame time recording, and the report written when a benchmark run ends.
+
+use amethyst::{
+    core::{bundle::SystemBundle, timing::Time},
+    ecs::{prelude::DispatcherBuilder, Read, System, WriteExpect},
+    error::Error,
+    prelude::{World, WorldExt},
+};
+use log::{error, info};
+use serde::Serialize;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ball_count,
+    collision::BroadphaseKind,
+    timing::{FrameClock, SystemTimings},
+    Scenario, VELOCITY_STORAGE,
+};
+
+/// Records frame times while benchmarking and writes the report when the run ends.
+pub struct BenchmarkBundle {
+    output: PathBuf,
+    warmup_frames: u64,
+}
+
+impl BenchmarkBundle {
+    /// Writes the report to `output`, leaving out the first `warmup_frames` frames.
+    pub fn new(output: PathBuf, warmup_frames: u64) -> Self {
+        Self {
+            output,
+            warmup_frames,
+        }
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for BenchmarkBundle {
+    fn build(
+        self,
+        world: &mut World,
+        builder: &mut DispatcherBuilder<'a, 'b>,
+    ) -> Result<(), Error> {
+        world.insert(Benchmark::new(self.output, self.warmup_frames));
+        // Thread-local, as `SystemTimings` asks.
+        builder.add_thread_local(FrameTimeSystem);
+
+        Ok(())
+    }
+}
+
+/// Frame times collected so far, in seconds, once the warm-up frames have passed, along with the
+/// total time and number of runs of every timed system.
+pub struct Benchmark {
+    output: PathBuf,
+    warmup_frames: u64,
+    frame_times: Vec<f32>,
+    system_times: Vec<(&'static str, f64, usize)>,
+}
+
+impl Benchmark {
+    /// Collects frame times for a report written to `output`, once `warmup_frames` have passed.
+    pub fn new(output: PathBuf, warmup_frames: u64) -> Self {
+        Self {
+            output,
+            warmup_frames,
+            frame_times: Vec::new(),
+            system_times: Vec::new(),
+        }
+    }
+
+    /// Adds how long a frame took, unless it is still warming up.
+    pub fn record(&mut self, frame_number: u64, delta_seconds: f32) {
+        if frame_number >= self.warmup_frames {
+            self.frame_times.push(delta_seconds);
+        }
+    }
+
+    /// Adds the time the system called `name` took in a frame.
+    pub fn record_system(&mut self, frame_number: u64, name: &'static str, seconds: f32) {
+        if frame_number < self.warmup_frames {
+            return;
+        }
+
+        match self
+            .system_times
+            .iter_mut()
+            .find(|(recorded, _, _)| *recorded == name)
+        {
+            Some((_, total, runs)) => {
+                *total += f64::from(seconds);
+                *runs += 1;
+            }
+            None => self.system_times.push((name, f64::from(seconds), 1)),
+        }
+    }
+
+    /// Summarises the recorded frames, or `None` if the run ended during warm-up.
+    pub fn report(
+        &self,
+        entity_count: usize,
+        total_seconds: f64,
+        broadphase: Option<BroadphaseKind>,
+    ) -> Option<BenchmarkReport> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+
+        let mut sorted = self.frame_times.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).expect("frame times are never NaN"));
+
+        let mean = sorted.iter().map(|&t| f64::from(t)).sum::<f64>() / sorted.len() as f64;
+
+        Some(BenchmarkReport {
+            entity_count,
+            frames: sorted.len(),
+            warmup_frames: self.warmup_frames,
+            mean_fps: 1.0 / mean,
+            p50_ms: percentile(&sorted, 0.50) * 1000.0,
+            p95_ms: percentile(&sorted, 0.95) * 1000.0,
+            p99_ms: percentile(&sorted, 0.99) * 1000.0,
+            total_seconds,
+            broadphase: broadphase.map(BroadphaseKind::name),
+            velocity_storage: VELOCITY_STORAGE,
+            systems: self
+                .system_times
+                .iter()
+                .map(|&(name, total, runs)| SystemReport {
+                    name,
+                    mean_ms: total / runs as f64 * 1000.0,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Nearest-rank percentile of an already sorted, non-empty slice.
+fn percentile(sorted: &[f32], fraction: f64) -> f64 {
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+
+    f64::from(sorted[rank.max(1) - 1])
+}
+
+/// Summary of a benchmark run, written as JSON or CSV.
+#[derive(Debug, Serialize)]
+pub struct BenchmarkReport {
+    /// Balls alive when the run ended.
+    pub entity_count: usize,
+    /// Frames measured, not counting warm-up.
+    pub frames: usize,
+    /// Frames left out at the start.
+    pub warmup_frames: u64,
+    /// Frames per second over the measured frames.
+    pub mean_fps: f64,
+    /// Median frame time.
+    pub p50_ms: f64,
+    /// 95th percentile frame time.
+    pub p95_ms: f64,
+    /// 99th percentile frame time.
+    pub p99_ms: f64,
+    /// Wall-clock time of the whole run, warm-up included.
+    pub total_seconds: f64,
+    /// Broadphase used for collisions between balls, if they collided.
+    pub broadphase: Option<&'static str>,
+    /// Storage `Velocity` was built to live in.
+    pub velocity_storage: &'static str,
+    /// Mean time of every timed system, empty unless the systems were timed.
+    pub systems: Vec<SystemReport>,
+}
+
+/// How long a timed system took on average in a `BenchmarkReport`.
+#[derive(Debug, Serialize)]
+pub struct SystemReport {
+    /// Name the system was dispatched as.
+    pub name: &'static str,
+    /// Mean time it ran for each frame.
+    pub mean_ms: f64,
+}
+
+impl BenchmarkReport {
+    /// Writes the report as CSV if the path ends in `.csv`, and as JSON otherwise.
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        let contents = if path.extension().map_or(false, |ext| ext == "csv") {
+            // Each timed system gets a column of its own, named after it.
+            let (system_names, system_times) = self.systems.iter().fold(
+                (String::new(), String::new()),
+                |(mut names, mut times), system| {
+                    names.push_str(&format!(",{}_ms", system.name));
+                    times.push_str(&format!(",{}", system.mean_ms));
+                    (names, times)
+                },
+            );
+
+            format!(
+                "entity_count,frames,warmup_frames,mean_fps,p50_ms,p95_ms,p99_ms,total_seconds,\
+                 broadphase,velocity_storage{}\n\
+                 {},{},{},{},{},{},{},{},{},{}{}\n",
+                system_names,
+                self.entity_count,
+                self.frames,
+                self.warmup_frames,
+                self.mean_fps,
+                self.p50_ms,
+                self.p95_ms,
+                self.p99_ms,
+                self.total_seconds,
+                self.broadphase.unwrap_or("none"),
+                self.velocity_storage,
+                system_times
+            )
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+}
+
+/// Writes the benchmark report for the run, if one is being recorded. Called as the game stops.
+pub fn finish(world: &World) {
+    let benchmark = match world.try_fetch::<Benchmark>() {
+        Some(benchmark) => benchmark,
+        None => return,
+    };
+
+    let entity_count = ball_count(world);
+    let total_seconds = world.read_resource::<Time>().absolute_real_time_seconds();
+    let broadphase = world
+        .read_resource::<Scenario>()
+        .collisions
+        .as_ref()
+        .map(|collisions| collisions.broadphase);
+
+    match benchmark.report(entity_count, total_seconds, broadphase) {
+        Some(report) => match report.write(&benchmark.output) {
+            Ok(()) => info!("Wrote benchmark report to {}", benchmark.output.display()),
+            Err(err) => error!("Failed to write benchmark report: {}", err),
+        },
+        None => error!("The run ended before warm-up finished, so there is nothing to report"),
+    }
+}
+
+/// Records the real frame time from `FrameClock` and the latest system timings.
+struct FrameTimeSystem;
+
+impl<'s> System<'s> for FrameTimeSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        Read<'s, FrameClock>,
+        Read<'s, SystemTimings>,
+        WriteExpect<'s, Benchmark>,
+    );
+
+    fn run(&mut self, (time, clock, timings, mut benchmark): Self::SystemData) {
+        if let Some(delta_seconds) = clock.delta_seconds(time.frame_number()) {
+            benchmark.record(time.frame_number(), delta_seconds);
+        }
+
+        for (name, seconds) in timings.latest_seconds() {
+            benchmark.record_system(time.frame_number(), name, seconds);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Bouncing the balls off the walls of the arena.
+
+use amethyst::ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+
+use std::marker::PhantomData;
+
+use crate::{
+    arena::ArenaBounds,
+    boundary::{Boundaries, Crossing, WallPolicy},
+    extent::Extent,
+    material::BounceMaterial,
+    position::Position,
+    velocity::{self, Velocity},
+};
+
+/// Applies the `Boundaries` at the walls of the `ArenaBounds`, in parallel when `parallel` is set,
+/// the same way as `MovementSystem`. Balls with a `BounceMaterial` component reflect with it, and
+/// the rest with the `BounceMaterial` resource. Balls with an `Extent` reach a wall when the edge
+/// of their sprite does, and the rest when their position `P` does. Absorbed balls are deleted.
+pub struct BounceSystem<P> {
+    parallel: bool,
+    marker: PhantomData<P>,
+}
+
+impl<P> BounceSystem<P> {
+    /// Bounces the balls on the dispatcher's thread pool when `parallel` is set.
+    pub fn new(parallel: bool) -> Self {
+        Self {
+            parallel,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, P: Position> System<'s> for BounceSystem<P> {
+    type SystemData = (
+        Entities<'s>,
+        ReadExpect<'s, ArenaBounds>,
+        Read<'s, Boundaries>,
+        Read<'s, BounceMaterial>,
+        ReadStorage<'s, BounceMaterial>,
+        ReadStorage<'s, Extent>,
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, P>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            arena,
+            boundaries,
+            wall_material,
+            materials,
+            extents,
+            mut velocities,
+            mut positions,
+        ): Self::SystemData,
+    ) {
+        let arena: &ArenaBounds = &arena;
+        let boundaries: &Boundaries = &boundaries;
+        let wall_material: &BounceMaterial = &wall_material;
+
+        let bounce = |(position, (entity, velocity, material, extent)): (
+            &mut P,
+            (
+                Entity,
+                &mut Velocity,
+                Option<&BounceMaterial>,
+                Option<&Extent>,
+            ),
+        )| {
+            let material = material.unwrap_or(wall_material);
+
+            if bounce_ball(position, velocity, extent, arena, boundaries, material) {
+                Some(entity)
+            } else {
+                None
+            }
+        };
+
+        let rest = (
+            &*entities,
+            &mut velocities,
+            materials.maybe(),
+            extents.maybe(),
+        );
+
+        let absorbed = if self.parallel {
+            velocity::par_filter_map(&mut positions, rest, bounce)
+        } else {
+            (&mut positions, rest)
+                .join()
+                .filter_map(bounce)
+                .collect::<Vec<_>>()
+        };
+
+        for entity in absorbed {
+            entities
+                .delete(entity)
+                .expect("the entity was just joined, so it is alive");
+        }
+    }
+}
+
+/// Applies the walls' policies to a ball. Returns whether a wall absorbed it.
+pub fn bounce_ball<P: Position>(
+    position: &mut P,
+    velocity: &mut Velocity,
+    extent: Option<&Extent>,
+    arena: &ArenaBounds,
+    boundaries: &Boundaries,
+    material: &BounceMaterial,
+) -> bool {
+    let [scale_x, scale_y] = position.scale();
+    let (half_width, half_height, offset_x, offset_y) = match extent {
+        Some(extent) => (
+            extent.half_width * scale_x.abs(),
+            extent.half_height * scale_y.abs(),
+            extent.offset[0] * scale_x,
+            extent.offset[1] * scale_y,
+        ),
+        None => (0.0, 0.0, 0.0, 0.0),
+    };
+
+    // The sprite is drawn centred on the position minus its offset.
+    let [x, y] = position.xy();
+    let centre_y = y - offset_y;
+    let centre_x = x - offset_x;
+
+    match bounce_axis(
+        centre_y,
+        (arena.min_y + half_height, arena.max_y - half_height),
+        (arena.min_y, arena.max_y),
+        (boundaries.bottom, boundaries.top),
+        &mut velocity.y,
+        &mut velocity.x,
+        material,
+    ) {
+        Crossing::None => {}
+        Crossing::Moved(centre_y) => position.set_y(centre_y + offset_y),
+        Crossing::Absorbed => return true,
+    }
+
+    match bounce_axis(
+        centre_x,
+        (arena.min_x + half_width, arena.max_x - half_width),
+        (arena.min_x, arena.max_x),
+        (boundaries.left, boundaries.right),
+        &mut velocity.x,
+        &mut velocity.y,
+        material,
+    ) {
+        Crossing::None => false,
+        Crossing::Moved(centre_x) => {
+            position.set_x(centre_x + offset_x);
+            false
+        }
+        Crossing::Absorbed => true,
+    }
+}
+
+/// Applies the policy of the wall a position reached along one axis, if it reached one.
+///
+/// The ball touches the walls at `low` and `high`, and wraps once it crosses the arena edges
+/// `min` and `max`. A reflecting wall keeps the position between `low` and `high`, reflecting the
+/// velocity off the wall if it is still heading into it.
+fn bounce_axis(
+    position: f32,
+    (low, high): (f32, f32),
+    (min, max): (f32, f32),
+    (low_wall, high_wall): (WallPolicy, WallPolicy),
+    normal: &mut f32,
+    tangent: &mut f32,
+    material: &BounceMaterial,
+) -> Crossing {
+    let (wall, edge, heading_out) = if position >= high {
+        (high_wall, high, *normal > 0.0)
+    } else if position <= low {
+        (low_wall, low, *normal < 0.0)
+    } else {
+        return Crossing::None;
+    };
+
+    match wall {
+        WallPolicy::Reflect => {
+            if heading_out {
+                material.reflect(normal, tangent);
+            }
+            Crossing::Moved(edge)
+        }
+        WallPolicy::Wrap if position > max => Crossing::Moved(position - (max - min)),
+        WallPolicy::Wrap if position < min => Crossing::Moved(position + (max - min)),
+        WallPolicy::Wrap | WallPolicy::Open => Crossing::None,
+        WallPolicy::Absorb => Crossing::Absorbed,
+    }
+}
@@ -0,0 +1,49 @@
+//! What each wall of the arena does to the balls that reach it.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to a ball that reaches a wall.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum WallPolicy {
+    /// Bounces the ball back with the `BounceMaterial`.
+    Reflect,
+    /// Moves the ball to the opposite side of the arena once its centre crosses the wall, making
+    /// the arena periodic along that axis.
+    Wrap,
+    /// Removes the ball as soon as it touches the wall.
+    Absorb,
+    /// Lets the ball leave the arena and carry on.
+    Open,
+}
+
+impl Default for WallPolicy {
+    fn default() -> Self {
+        WallPolicy::Reflect
+    }
+}
+
+/// The policy for each wall of the `ArenaBounds`. Every wall reflects unless the scenario says
+/// otherwise.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Boundaries {
+    /// The wall at `min_x`.
+    pub left: WallPolicy,
+    /// The wall at `max_x`.
+    pub right: WallPolicy,
+    /// The wall at `min_y`.
+    pub bottom: WallPolicy,
+    /// The wall at `max_y`.
+    pub top: WallPolicy,
+}
+
+/// What the walls along one axis did to a ball.
+#[derive(Debug, PartialEq)]
+pub enum Crossing {
+    /// The ball stays where it is.
+    None,
+    /// The ball moves to this position along the axis.
+    Moved(f32),
+    /// The ball is removed.
+    Absorbed,
+}
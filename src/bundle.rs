@@ -0,0 +1,321 @@
+//! The bundle that adds the simulation systems to a dispatcher.
+
+use amethyst::{
+    core::{bundle::SystemBundle, transform::Transform},
+    ecs::{prelude::DispatcherBuilder, System},
+    error::Error,
+    prelude::World,
+};
+
+use crate::{
+    arena::ArenaBounds,
+    bounce::BounceSystem,
+    collision::{BroadphaseKind, CollisionSystem},
+    extent::SpriteExtentSystem,
+    forces::AccelerationSystem,
+    integrate::IntegrateBounceSystem,
+    movement::MovementSystem,
+    position::{Position, Position2D, Position2DSyncSystem, PositionMode},
+    timestep::{FixedTimestep, FixedTimestepSystem},
+    timing::Timed,
+    window::WindowResizeSystem,
+};
+
+/// Adds the systems that move the balls and bounce them off the walls of the `ArenaBounds`, set up
+/// with its `with_*` methods.
+///
+/// Balls need a `Velocity` and a `Transform`, or a `Position2D` when the bundle uses one. The
+/// `BounceMaterial`, `Boundaries` and `Gravity` resources are optional, and default to perfect
+/// bounces off reflecting walls without gravity.
+pub struct BounceBundle {
+    /// Whether there is a window for the camera to follow when it is resized.
+    windowed: bool,
+    /// Whether movement and bouncing are spread across threads.
+    parallel: bool,
+    /// Whether movement and bouncing share a single pass over the balls.
+    fused: bool,
+    /// Fixed step to advance the simulation by, instead of the frame time.
+    fixed_timestep: Option<FixedTimestep>,
+    /// Walls to bounce off, instead of the edges of the window.
+    arena: Option<ArenaBounds>,
+    /// How pairs of balls with a `Radius` are found, if they collide with each other at all.
+    collisions: Option<BroadphaseKind>,
+    /// Which component the balls are moved with.
+    position_mode: PositionMode,
+    /// Whether each system records how long it runs in `SystemTimings`.
+    timings: bool,
+}
+
+impl BounceBundle {
+    /// A bundle for a windowed game, moving `Transform`s on a single thread by the frame time,
+    /// with the arena following the window and no collisions between balls.
+    pub fn new() -> Self {
+        Self {
+            windowed: true,
+            parallel: false,
+            fused: false,
+            fixed_timestep: None,
+            arena: None,
+            collisions: None,
+            position_mode: PositionMode::Transform,
+            timings: false,
+        }
+    }
+
+    /// Runs movement and bouncing in parallel instead of on a single thread.
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Replaces `MovementSystem` and `BounceSystem` with `IntegrateBounceSystem`, which does the
+    /// work of both in one pass.
+    pub fn with_fused(mut self, fused: bool) -> Self {
+        self.fused = fused;
+        self
+    }
+
+    /// Advances the simulation in fixed steps instead of by the frame time. Each sub-step has to
+    /// bounce before the next one moves, so this always uses the fused pass.
+    pub fn with_fixed_timestep(mut self, fixed_timestep: Option<FixedTimestep>) -> Self {
+        self.fixed_timestep = fixed_timestep;
+        self
+    }
+
+    /// Bounces the balls off these walls. Without them, the arena is kept the size of the window.
+    pub fn with_arena(mut self, arena: Option<ArenaBounds>) -> Self {
+        self.arena = arena;
+        self
+    }
+
+    /// Bounces balls with a `Radius` off each other after they move, finding the pairs that might
+    /// touch with the given broadphase.
+    pub fn with_collisions(mut self, collisions: Option<BroadphaseKind>) -> Self {
+        self.collisions = collisions;
+        self
+    }
+
+    /// Moves the balls with a `Position2D` instead of their `Transform`. Rendered balls still have
+    /// a `Transform`, which is kept in sync.
+    pub fn with_position_mode(mut self, position_mode: PositionMode) -> Self {
+        self.position_mode = position_mode;
+        self
+    }
+
+    /// Times every system the bundle adds, recording how long each took in the `SystemTimings`
+    /// resource every frame.
+    pub fn with_timings(mut self, timings: bool) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Leaves out the systems that need a window, so the bundle can run without rendering. The
+    /// arena has to be given with `with_arena`.
+    pub fn headless(mut self) -> Self {
+        self.windowed = false;
+        self
+    }
+}
+
+impl Default for BounceBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adds a system, wrapped in `Timed` if `timings` is set.
+fn add_system<'a, S>(
+    builder: &mut DispatcherBuilder<'a, '_>,
+    timings: bool,
+    system: S,
+    name: &'static str,
+    dependencies: &[&str],
+) where
+    S: for<'c> System<'c> + Send + 'a,
+    Timed<S>: for<'c> System<'c>,
+{
+    if timings {
+        builder.add(Timed::new(name, system), name, dependencies);
+    } else {
+        builder.add(system, name, dependencies);
+    }
+}
+
+/// Adds the systems that accelerate, move, bounce and collide the balls, running on the position
+/// component `P`, and bouncing them only after the systems in `dependencies`. Returns the name of
+/// the system that runs last.
+fn add_simulation<P: Position>(
+    builder: &mut DispatcherBuilder<'_, '_>,
+    timings: bool,
+    parallel: bool,
+    collisions: Option<BroadphaseKind>,
+    single_pass: bool,
+    dependencies: &[&str],
+) -> &'static str {
+    if single_pass {
+        add_system(
+            builder,
+            timings,
+            IntegrateBounceSystem::<P>::new(parallel),
+            "integrate_bounce_system",
+            dependencies,
+        );
+
+        // Collisions are resolved once per frame, after every step has run. Balls pushed apart
+        // may end up in a wall, so the walls get another pass afterwards.
+        if let Some(broadphase) = collisions {
+            add_system(
+                builder,
+                timings,
+                CollisionSystem::<P>::new(broadphase),
+                "collision_system",
+                &["integrate_bounce_system"],
+            );
+            add_system(
+                builder,
+                timings,
+                BounceSystem::<P>::new(parallel),
+                "collision_bounce_system",
+                &["collision_system"],
+            );
+
+            "collision_bounce_system"
+        } else {
+            "integrate_bounce_system"
+        }
+    } else {
+        add_system(
+            builder,
+            timings,
+            AccelerationSystem::new(parallel),
+            "acceleration_system",
+            &[],
+        );
+        add_system(
+            builder,
+            timings,
+            MovementSystem::<P>::new(parallel),
+            "movement_system",
+            &["acceleration_system"],
+        );
+
+        let mut bounce_dependencies = dependencies.to_vec();
+        bounce_dependencies.push("movement_system");
+
+        if let Some(broadphase) = collisions {
+            add_system(
+                builder,
+                timings,
+                CollisionSystem::<P>::new(broadphase),
+                "collision_system",
+                &["movement_system"],
+            );
+            bounce_dependencies.push("collision_system");
+        }
+
+        add_system(
+            builder,
+            timings,
+            BounceSystem::<P>::new(parallel),
+            "bounce_system",
+            &bounce_dependencies,
+        );
+
+        "bounce_system"
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for BounceBundle {
+    fn build(
+        self,
+        world: &mut World,
+        builder: &mut DispatcherBuilder<'a, 'b>,
+    ) -> Result<(), Error> {
+        let follow_window = self.arena.is_none();
+
+        match self.arena {
+            Some(arena) => world.insert(arena),
+            None if !self.windowed => {
+                return Err(Error::from_string(
+                    "a headless BounceBundle needs arena bounds",
+                ))
+            }
+            None => {}
+        }
+
+        if self.windowed {
+            add_system(
+                builder,
+                self.timings,
+                WindowResizeSystem::new(follow_window),
+                "window_resize_system",
+                &[],
+            );
+            add_system(
+                builder,
+                self.timings,
+                SpriteExtentSystem,
+                "sprite_extent_system",
+                &[],
+            );
+        }
+
+        let single_pass = self.fused || self.fixed_timestep.is_some();
+        // Bouncing waits for the arena to follow the window.
+        let mut dependencies = Vec::new();
+
+        if self.windowed {
+            dependencies.push("window_resize_system");
+        }
+
+        if let Some(fixed_timestep) = self.fixed_timestep {
+            world.insert(fixed_timestep);
+            add_system(
+                builder,
+                self.timings,
+                FixedTimestepSystem,
+                "fixed_timestep_system",
+                &[],
+            );
+            dependencies.push("fixed_timestep_system");
+        }
+
+        match self.position_mode {
+            PositionMode::Transform => {
+                add_simulation::<Transform>(
+                    builder,
+                    self.timings,
+                    self.parallel,
+                    self.collisions,
+                    single_pass,
+                    &dependencies,
+                );
+            }
+            PositionMode::Position2D => {
+                let last = add_simulation::<Position2D>(
+                    builder,
+                    self.timings,
+                    self.parallel,
+                    self.collisions,
+                    single_pass,
+                    &dependencies,
+                );
+
+                // Headless balls have no `Transform` to keep in sync.
+                if self.windowed {
+                    add_system(
+                        builder,
+                        self.timings,
+                        Position2DSyncSystem,
+                        "position_2d_sync_system",
+                        &[last],
+                    );
+                }
+            }
+        }
+
+        world.insert(self.position_mode);
+
+        Ok(())
+    }
+}
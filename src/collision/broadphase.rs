@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Body, BruteForce, Quadtree, SweepAndPrune, UniformGrid};
+
+/// Finds the pairs of bodies that might be touching, so the narrowphase doesn't have to test
+/// every pair.
+pub trait Broadphase: Send + Sync {
+    /// Fills `pairs` with every pair of bodies that might overlap, each once with the lower index
+    /// first. The order may differ between broadphases, but must depend only on `bodies`, so
+    /// that runs stay deterministic.
+    fn find_pairs(&mut self, bodies: &[Body], pairs: &mut Vec<(usize, usize)>);
+}
+
+/// Which `Broadphase` to collide with, chosen in the scenario.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum BroadphaseKind {
+    /// Tests every pair. Only sensible for small counts.
+    BruteForce,
+    /// Hashes the bodies into a grid of cells as wide as the largest body.
+    UniformGrid,
+    /// Sorts the bodies along x and only tests those whose x ranges overlap.
+    SweepAndPrune,
+    /// Stores the bodies in a quadtree and only tests those in overlapping quadrants.
+    Quadtree,
+}
+
+impl BroadphaseKind {
+    /// A new broadphase of this kind.
+    pub fn create(self) -> Box<dyn Broadphase> {
+        match self {
+            BroadphaseKind::BruteForce => Box::new(BruteForce),
+            BroadphaseKind::UniformGrid => Box::new(UniformGrid::default()),
+            BroadphaseKind::SweepAndPrune => Box::new(SweepAndPrune::default()),
+            BroadphaseKind::Quadtree => Box::new(Quadtree::default()),
+        }
+    }
+
+    /// Name of the broadphase in benchmark reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            BroadphaseKind::BruteForce => "brute_force",
+            BroadphaseKind::UniformGrid => "uniform_grid",
+            BroadphaseKind::SweepAndPrune => "sweep_and_prune",
+            BroadphaseKind::Quadtree => "quadtree",
+        }
+    }
+}
+
+impl Default for BroadphaseKind {
+    fn default() -> Self {
+        BroadphaseKind::UniformGrid
+    }
+}
+
+/// The box around a body, used by the broadphases to rule pairs out cheaply.
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+    /// Bottom left corner.
+    pub min: [f32; 2],
+    /// Top right corner.
+    pub max: [f32; 2],
+}
+
+impl Aabb {
+    /// The box around a body's circle.
+    pub fn of(body: &Body) -> Self {
+        Self {
+            min: [
+                body.position[0] - body.radius,
+                body.position[1] - body.radius,
+            ],
+            max: [
+                body.position[0] + body.radius,
+                body.position[1] + body.radius,
+            ],
+        }
+    }
+
+    /// Whether the boxes overlap or touch.
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min[0] <= other.max[0]
+            && other.min[0] <= self.max[0]
+            && self.min[1] <= other.max[1]
+            && other.min[1] <= self.max[1]
+    }
+
+    /// Whether `other` lies wholly inside this box.
+    pub fn contains(&self, other: &Aabb) -> bool {
+        self.min[0] <= other.min[0]
+            && self.min[1] <= other.min[1]
+            && other.max[0] <= self.max[0]
+            && other.max[1] <= self.max[1]
+    }
+}
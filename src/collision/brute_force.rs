@@ -0,0 +1,21 @@
+use super::{Aabb, Body, Broadphase};
+
+/// Broadphase that tests the boxes of every pair of bodies. Quadratic, so only for small counts
+/// or for checking the others against.
+pub struct BruteForce;
+
+impl Broadphase for BruteForce {
+    fn find_pairs(&mut self, bodies: &[Body], pairs: &mut Vec<(usize, usize)>) {
+        pairs.clear();
+
+        let boxes = bodies.iter().map(Aabb::of).collect::<Vec<_>>();
+
+        for (index, aabb) in boxes.iter().enumerate() {
+            for (other, other_aabb) in boxes.iter().enumerate().skip(index + 1) {
+                if aabb.overlaps(other_aabb) {
+                    pairs.push((index, other));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use super::{Body, Broadphase};
+
+/// Broadphase that hashes bodies into square cells at least as wide as the largest body, so
+/// only bodies in the same or neighbouring cells can touch.
+#[derive(Default)]
+pub struct UniformGrid {
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Broadphase for UniformGrid {
+    fn find_pairs(&mut self, bodies: &[Body], pairs: &mut Vec<(usize, usize)>) {
+        pairs.clear();
+
+        let cell_size = bodies
+            .iter()
+            .map(|body| body.radius * 2.0)
+            .fold(0.0, f32::max);
+
+        if cell_size <= 0.0 {
+            return;
+        }
+
+        // Keep the allocations of the cells used last frame, but forget which bodies were in them.
+        // Cells nobody used are dropped, so balls leaving through open walls don't grow the map.
+        self.cells.retain(|_, cell| {
+            let used = !cell.is_empty();
+            cell.clear();
+            used
+        });
+
+        let cell_of = |body: &Body| {
+            (
+                (body.position[0] / cell_size).floor() as i32,
+                (body.position[1] / cell_size).floor() as i32,
+            )
+        };
+
+        for (index, body) in bodies.iter().enumerate() {
+            self.cells.entry(cell_of(body)).or_default().push(index);
+        }
+
+        for (index, body) in bodies.iter().enumerate() {
+            let (cell_x, cell_y) = cell_of(body);
+
+            for neighbour_x in cell_x - 1..=cell_x + 1 {
+                for neighbour_y in cell_y - 1..=cell_y + 1 {
+                    if let Some(cell) = self.cells.get(&(neighbour_x, neighbour_y)) {
+                        pairs.extend(
+                            cell.iter()
+                                .filter(|&&other| other > index)
+                                .map(|&other| (index, other)),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
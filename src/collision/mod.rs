@@ -0,0 +1,345 @@
+//! Collisions between balls, with a choice of broadphase to find the pairs worth testing.
+
+use amethyst::ecs::{Component, DenseVecStorage, Join, Read, ReadStorage, System, WriteStorage};
+use serde::{Deserialize, Serialize};
+
+use std::marker::PhantomData;
+
+use crate::{position::Position, timestep::FixedTimestep, Velocity};
+
+mod broadphase;
+mod brute_force;
+mod grid;
+mod quadtree;
+mod sweep_and_prune;
+
+pub use self::{
+    broadphase::{Aabb, Broadphase, BroadphaseKind},
+    brute_force::BruteForce,
+    grid::UniformGrid,
+    quadtree::Quadtree,
+    sweep_and_prune::SweepAndPrune,
+};
+
+/// Gives spawned balls a `Radius` and `Mass` so they collide with each other.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CollisionConfig {
+    /// Radius given to every ball.
+    pub radius: f32,
+    /// Mass given to every ball.
+    pub mass: f32,
+    /// How the pairs of balls that might touch are found.
+    pub broadphase: BroadphaseKind,
+}
+
+impl Default for CollisionConfig {
+    fn default() -> Self {
+        Self {
+            radius: 2.0,
+            mass: 1.0,
+            broadphase: BroadphaseKind::default(),
+        }
+    }
+}
+
+/// Size of a ball for collisions with other balls. Balls without one pass through the others.
+#[derive(Clone, Debug)]
+pub struct Radius(pub f32);
+
+impl Component for Radius {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Mass of a ball in collisions. Balls with a `Radius` but no `Mass` weigh `1.0`.
+#[derive(Clone, Debug)]
+pub struct Mass(pub f32);
+
+impl Component for Mass {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A ball's state copied out of its components while collisions are resolved.
+#[derive(Clone, Copy, Debug)]
+pub struct Body {
+    /// Position of the ball.
+    pub position: [f32; 2],
+    /// Velocity of the ball.
+    pub velocity: [f32; 2],
+    /// Its `Radius`.
+    pub radius: f32,
+    /// One over its `Mass`, so heavier balls are pushed less.
+    pub inverse_mass: f32,
+}
+
+/// Bounces balls with a `Radius` off each other elastically, and pushes overlapping balls apart.
+/// Runs after the balls have moved their position `P`, and not at all on frames where a
+/// `FixedTimestep` has no steps due.
+pub struct CollisionSystem<P> {
+    broadphase: Box<dyn Broadphase>,
+    bodies: Vec<Body>,
+    pairs: Vec<(usize, usize)>,
+    marker: PhantomData<P>,
+}
+
+impl<P> CollisionSystem<P> {
+    /// Finds the pairs to test with a broadphase of the given kind.
+    pub fn new(broadphase: BroadphaseKind) -> Self {
+        Self {
+            broadphase: broadphase.create(),
+            bodies: Vec::new(),
+            pairs: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, P: Position> System<'s> for CollisionSystem<P> {
+    type SystemData = (
+        ReadStorage<'s, Radius>,
+        ReadStorage<'s, Mass>,
+        WriteStorage<'s, P>,
+        WriteStorage<'s, Velocity>,
+        Option<Read<'s, FixedTimestep>>,
+    );
+
+    fn run(
+        &mut self,
+        (radii, masses, mut positions, mut velocities, fixed_timestep): Self::SystemData,
+    ) {
+        // Nothing moved, so nothing new can touch.
+        if fixed_timestep.map_or(false, |fixed_timestep| fixed_timestep.substeps() == 0) {
+            return;
+        }
+
+        self.bodies.clear();
+        self.bodies.extend(
+            (&radii, masses.maybe(), &positions, &velocities)
+                .join()
+                .map(|(radius, mass, position, velocity)| Body {
+                    position: position.xy(),
+                    velocity: [velocity.x, velocity.y],
+                    radius: radius.0,
+                    inverse_mass: 1.0 / mass.map_or(1.0, |mass| mass.0),
+                }),
+        );
+
+        self.broadphase.find_pairs(&self.bodies, &mut self.pairs);
+
+        let mut collided = false;
+
+        for &(a, b) in &self.pairs {
+            collided |= resolve(&mut self.bodies, a, b);
+        }
+
+        if !collided {
+            return;
+        }
+
+        // The join visits the balls in the same order as when the bodies were collected.
+        for (body, (_, position, velocity)) in self
+            .bodies
+            .iter()
+            .zip((&radii, &mut positions, &mut velocities).join())
+        {
+            position.set_x(body.position[0]);
+            position.set_y(body.position[1]);
+            velocity.x = body.velocity[0];
+            velocity.y = body.velocity[1];
+        }
+    }
+}
+
+/// Narrowphase and response for one pair: if the circles overlap, they are pushed apart in
+/// proportion to their inverse masses and, if they are approaching, exchange an elastic impulse.
+/// Returns whether they touched.
+fn resolve(bodies: &mut [Body], a: usize, b: usize) -> bool {
+    let (first, second) = (bodies[a], bodies[b]);
+
+    let delta = [
+        second.position[0] - first.position[0],
+        second.position[1] - first.position[1],
+    ];
+    let distance_squared = delta[0] * delta[0] + delta[1] * delta[1];
+    let reach = first.radius + second.radius;
+
+    if distance_squared >= reach * reach {
+        return false;
+    }
+
+    let total_inverse_mass = first.inverse_mass + second.inverse_mass;
+    if total_inverse_mass <= 0.0 {
+        return false;
+    }
+
+    let distance = distance_squared.sqrt();
+    // Balls exactly on top of each other have no direction between them, so pick one.
+    let normal = if distance > 0.0 {
+        [delta[0] / distance, delta[1] / distance]
+    } else {
+        [1.0, 0.0]
+    };
+
+    let push = (reach - distance) / total_inverse_mass;
+    let approach = (second.velocity[0] - first.velocity[0]) * normal[0]
+        + (second.velocity[1] - first.velocity[1]) * normal[1];
+    let impulse = if approach < 0.0 {
+        -2.0 * approach / total_inverse_mass
+    } else {
+        0.0
+    };
+
+    let first = &mut bodies[a];
+    first.position[0] -= normal[0] * push * first.inverse_mass;
+    first.position[1] -= normal[1] * push * first.inverse_mass;
+    first.velocity[0] -= normal[0] * impulse * first.inverse_mass;
+    first.velocity[1] -= normal[1] * impulse * first.inverse_mass;
+
+    let second = &mut bodies[b];
+    second.position[0] += normal[0] * push * second.inverse_mass;
+    second.position[1] += normal[1] * push * second.inverse_mass;
+    second.velocity[0] += normal[0] * impulse * second.inverse_mass;
+    second.velocity[1] += normal[1] * impulse * second.inverse_mass;
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    fn random_bodies(rng: &mut Pcg32, count: usize) -> Vec<Body> {
+        (0..count)
+            .map(|_| Body {
+                position: [rng.gen_range(-100.0, 100.0), rng.gen_range(-100.0, 100.0)],
+                velocity: [rng.gen_range(-10.0, 10.0), rng.gen_range(-10.0, 10.0)],
+                radius: rng.gen_range(0.5, 5.0),
+                inverse_mass: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn broadphases_find_the_same_pairs_as_brute_force() {
+        let kinds = [
+            BroadphaseKind::UniformGrid,
+            BroadphaseKind::SweepAndPrune,
+            BroadphaseKind::Quadtree,
+        ];
+
+        for &kind in &kinds {
+            let mut rng = Pcg32::seed_from_u64(7);
+            let mut broadphase = kind.create();
+
+            // Several frames, so broadphases that keep state between frames are checked too.
+            for _ in 0..5 {
+                let bodies = random_bodies(&mut rng, 300);
+
+                let mut expected = Vec::new();
+                BruteForce.find_pairs(&bodies, &mut expected);
+                expected.sort();
+
+                let mut pairs = Vec::new();
+                broadphase.find_pairs(&bodies, &mut pairs);
+                pairs.sort();
+
+                let before = pairs.len();
+                pairs.dedup();
+                assert_eq!(pairs.len(), before, "{} found a pair twice", kind.name());
+                assert!(pairs.iter().all(|&(a, b)| a < b));
+
+                // Pairs that can't touch are allowed, as long as none that can is missed.
+                pairs.retain(|&(a, b)| Aabb::of(&bodies[a]).overlaps(&Aabb::of(&bodies[b])));
+                assert_eq!(pairs, expected, "{} disagrees", kind.name());
+            }
+        }
+    }
+
+    fn momentum(bodies: &[Body]) -> [f32; 2] {
+        bodies.iter().fold([0.0, 0.0], |[x, y], body| {
+            [
+                x + body.velocity[0] / body.inverse_mass,
+                y + body.velocity[1] / body.inverse_mass,
+            ]
+        })
+    }
+
+    fn kinetic_energy(bodies: &[Body]) -> f32 {
+        bodies
+            .iter()
+            .map(|body| {
+                let [x, y] = body.velocity;
+                0.5 * (x * x + y * y) / body.inverse_mass
+            })
+            .sum()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() <= 1e-4 * expected.abs().max(1.0),
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn resolve_conserves_momentum_and_energy() {
+        let mut bodies = vec![
+            Body {
+                position: [0.0, 0.0],
+                velocity: [3.0, 1.0],
+                radius: 2.0,
+                inverse_mass: 1.0,
+            },
+            Body {
+                position: [3.0, 1.0],
+                velocity: [-2.0, 0.5],
+                radius: 2.0,
+                inverse_mass: 1.0 / 3.0,
+            },
+        ];
+
+        let momentum_before = momentum(&bodies);
+        let energy_before = kinetic_energy(&bodies);
+
+        assert!(resolve(&mut bodies, 0, 1));
+
+        let momentum_after = momentum(&bodies);
+        assert_close(momentum_after[0], momentum_before[0]);
+        assert_close(momentum_after[1], momentum_before[1]);
+        assert_close(kinetic_energy(&bodies), energy_before);
+
+        // The balls were pushed apart until they just touch.
+        let [dx, dy] = [
+            bodies[1].position[0] - bodies[0].position[0],
+            bodies[1].position[1] - bodies[0].position[1],
+        ];
+        assert_close(dx.hypot(dy), 4.0);
+    }
+
+    #[test]
+    fn resolve_ignores_balls_that_do_not_touch() {
+        let ball = Body {
+            position: [0.0, 0.0],
+            velocity: [1.0, 0.0],
+            radius: 1.0,
+            inverse_mass: 1.0,
+        };
+        let mut bodies = vec![
+            ball,
+            Body {
+                position: [5.0, 0.0],
+                velocity: [-1.0, 0.0],
+                ..ball
+            },
+        ];
+
+        assert!(!resolve(&mut bodies, 0, 1));
+        assert_eq!(bodies[0].velocity, [1.0, 0.0]);
+        assert_eq!(bodies[1].velocity, [-1.0, 0.0]);
+    }
+}
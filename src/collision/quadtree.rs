@@ -0,0 +1,149 @@
+use super::{Aabb, Body, Broadphase};
+
+/// Bodies a leaf holds before it is split into quarters.
+const MAX_ITEMS: usize = 8;
+/// Depth past which leaves are no longer split, however full they get.
+const MAX_DEPTH: u32 = 10;
+
+/// Broadphase that stores each body in the smallest quadrant that holds its box whole, and only
+/// tests bodies in quadrants that overlap.
+#[derive(Default)]
+pub struct Quadtree {
+    boxes: Vec<Aabb>,
+    nodes: Vec<Node>,
+    stack: Vec<usize>,
+}
+
+struct Node {
+    bounds: Aabb,
+    depth: u32,
+    /// Index of the first of the four children, which are stored next to each other.
+    first_child: Option<usize>,
+    items: Vec<usize>,
+}
+
+impl Node {
+    fn new(bounds: Aabb, depth: u32) -> Self {
+        Self {
+            bounds,
+            depth,
+            first_child: None,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl Quadtree {
+    fn insert(&mut self, index: usize) {
+        let aabb = self.boxes[index];
+        let mut node = 0;
+
+        while let Some(first_child) = self.nodes[node].first_child {
+            match (first_child..first_child + 4)
+                .find(|&child| self.nodes[child].bounds.contains(&aabb))
+            {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        self.nodes[node].items.push(index);
+
+        if self.nodes[node].first_child.is_none()
+            && self.nodes[node].items.len() > MAX_ITEMS
+            && self.nodes[node].depth < MAX_DEPTH
+        {
+            self.split(node);
+        }
+    }
+
+    /// Gives a leaf four children, moving down every item that fits wholly inside one of them.
+    fn split(&mut self, node: usize) {
+        let Aabb { min, max } = self.nodes[node].bounds;
+        let centre = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+        let depth = self.nodes[node].depth + 1;
+        let first_child = self.nodes.len();
+
+        for &(x, y) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let bounds = Aabb {
+                min: [
+                    if x == 0 { min[0] } else { centre[0] },
+                    if y == 0 { min[1] } else { centre[1] },
+                ],
+                max: [
+                    if x == 0 { centre[0] } else { max[0] },
+                    if y == 0 { centre[1] } else { max[1] },
+                ],
+            };
+            self.nodes.push(Node::new(bounds, depth));
+        }
+
+        self.nodes[node].first_child = Some(first_child);
+
+        let items = std::mem::take(&mut self.nodes[node].items);
+
+        for index in items {
+            let aabb = &self.boxes[index];
+
+            match (first_child..first_child + 4)
+                .find(|&child| self.nodes[child].bounds.contains(aabb))
+            {
+                Some(child) => self.nodes[child].items.push(index),
+                None => self.nodes[node].items.push(index),
+            }
+        }
+    }
+}
+
+impl Broadphase for Quadtree {
+    fn find_pairs(&mut self, bodies: &[Body], pairs: &mut Vec<(usize, usize)>) {
+        pairs.clear();
+        self.nodes.clear();
+
+        self.boxes.clear();
+        self.boxes.extend(bodies.iter().map(Aabb::of));
+
+        let bounds = match self.boxes.first() {
+            Some(first) => self.boxes.iter().fold(*first, |bounds, aabb| Aabb {
+                min: [
+                    bounds.min[0].min(aabb.min[0]),
+                    bounds.min[1].min(aabb.min[1]),
+                ],
+                max: [
+                    bounds.max[0].max(aabb.max[0]),
+                    bounds.max[1].max(aabb.max[1]),
+                ],
+            }),
+            None => return,
+        };
+
+        self.nodes.push(Node::new(bounds, 0));
+
+        for index in 0..bodies.len() {
+            self.insert(index);
+        }
+
+        for (index, aabb) in self.boxes.iter().enumerate() {
+            self.stack.clear();
+            self.stack.push(0);
+
+            while let Some(node) = self.stack.pop() {
+                let node = &self.nodes[node];
+
+                if !node.bounds.overlaps(aabb) {
+                    continue;
+                }
+
+                for &other in &node.items {
+                    if other > index && aabb.overlaps(&self.boxes[other]) {
+                        pairs.push((index, other));
+                    }
+                }
+
+                if let Some(first_child) = node.first_child {
+                    self.stack.extend(first_child..first_child + 4);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::cmp::Ordering;
+
+use super::{Aabb, Body, Broadphase};
+
+/// Broadphase that sorts the bodies along x and sweeps across them, only testing bodies whose
+/// x ranges overlap.
+#[derive(Default)]
+pub struct SweepAndPrune {
+    boxes: Vec<Aabb>,
+    order: Vec<usize>,
+    active: Vec<usize>,
+}
+
+impl Broadphase for SweepAndPrune {
+    fn find_pairs(&mut self, bodies: &[Body], pairs: &mut Vec<(usize, usize)>) {
+        pairs.clear();
+
+        self.boxes.clear();
+        self.boxes.extend(bodies.iter().map(Aabb::of));
+
+        let boxes = &self.boxes;
+
+        // Bodies starting at the same x are taken in index order, to keep the pairs in a
+        // repeatable order.
+        self.order.clear();
+        self.order.extend(0..bodies.len());
+        self.order.sort_by(|&a, &b| {
+            boxes[a].min[0]
+                .partial_cmp(&boxes[b].min[0])
+                .unwrap_or(Ordering::Equal)
+                .then(a.cmp(&b))
+        });
+
+        self.active.clear();
+
+        for &index in &self.order {
+            let aabb = &boxes[index];
+
+            self.active
+                .retain(|&other| boxes[other].max[0] >= aabb.min[0]);
+
+            for &other in &self.active {
+                if aabb.overlaps(&boxes[other]) {
+                    pairs.push((index.min(other), index.max(other)));
+                }
+            }
+
+            self.active.push(index);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! The size of the balls' sprites, so they bounce where their edges touch a wall.
+
+use amethyst::{
+    assets::AssetStorage,
+    ecs::{Component, DenseVecStorage, Entities, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::sprite::{Sprite, SpriteRender, SpriteSheet},
+};
+
+/// The size of a ball's sprite in pixels, before the `Transform` scale is applied.
+///
+/// Balls without one are treated as points.
+#[derive(Clone, Debug)]
+pub struct Extent {
+    /// Half the sprite's width.
+    pub half_width: f32,
+    /// Half the sprite's height.
+    pub half_height: f32,
+    /// How far the sprite is drawn from the ball's translation, like `Sprite::offsets`.
+    pub offset: [f32; 2],
+}
+
+impl Extent {
+    /// The extent of a sprite from its sheet.
+    pub fn from_sprite(sprite: &Sprite) -> Self {
+        Self {
+            half_width: sprite.width / 2.0,
+            half_height: sprite.height / 2.0,
+            offset: sprite.offsets,
+        }
+    }
+}
+
+impl Component for Extent {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Gives balls an `Extent` from their sprite, once its sprite sheet has finished loading.
+pub struct SpriteExtentSystem;
+
+impl<'s> System<'s> for SpriteExtentSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SpriteRender>,
+        Read<'s, AssetStorage<SpriteSheet>>,
+        WriteStorage<'s, Extent>,
+    );
+
+    fn run(&mut self, (entities, sprites, sprite_sheets, mut extents): Self::SystemData) {
+        let loaded = (&entities, &sprites, !&extents)
+            .join()
+            .filter_map(|(entity, sprite, _)| {
+                let sprite_sheet = sprite_sheets.get(&sprite.sprite_sheet)?;
+                let extent = Extent::from_sprite(sprite_sheet.sprites.get(sprite.sprite_number)?);
+
+                Some((entity, extent))
+            })
+            .collect::<Vec<_>>();
+
+        for (entity, extent) in loaded {
+            extents
+                .insert(entity, extent)
+                .expect("the entity was just joined, so it is alive");
+        }
+    }
+}
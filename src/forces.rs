@@ -0,0 +1,91 @@
+//! Gravity and the acceleration of individual balls.
+
+use amethyst::{
+    core::timing::Time,
+    ecs::{Component, DenseVecStorage, Join, Read, ReadStorage, System, WriteStorage},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::velocity::{self, Velocity};
+
+/// Acceleration applied to every ball, in pixels per second squared.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Gravity {
+    /// Horizontal acceleration.
+    pub x: f32,
+    /// Vertical acceleration.
+    pub y: f32,
+}
+
+impl Gravity {
+    /// Whether there is no gravity at all.
+    pub fn is_zero(&self) -> bool {
+        self.x == 0.0 && self.y == 0.0
+    }
+}
+
+/// A constant acceleration on one ball, on top of `Gravity`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Acceleration {
+    /// Horizontal acceleration.
+    pub x: f32,
+    /// Vertical acceleration.
+    pub y: f32,
+}
+
+impl Component for Acceleration {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Speeds balls up by `Gravity` and their own `Acceleration`. Runs before `MovementSystem`.
+pub struct AccelerationSystem {
+    parallel: bool,
+}
+
+impl AccelerationSystem {
+    /// Accelerates the balls on the dispatcher's thread pool when `parallel` is set.
+    pub fn new(parallel: bool) -> Self {
+        Self { parallel }
+    }
+}
+
+impl<'s> System<'s> for AccelerationSystem {
+    type SystemData = (
+        Read<'s, Gravity>,
+        ReadStorage<'s, Acceleration>,
+        WriteStorage<'s, Velocity>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (gravity, accelerations, mut velocities, time): Self::SystemData) {
+        let delta_seconds = time.delta_seconds();
+        let (gravity_x, gravity_y) = (gravity.x, gravity.y);
+
+        let apply_gravity = |velocity: &mut Velocity| {
+            accelerate_ball(velocity, gravity_x, gravity_y, delta_seconds);
+        };
+        let accelerate = |(velocity, acceleration): (&mut Velocity, &Acceleration)| {
+            accelerate_ball(velocity, acceleration.x, acceleration.y, delta_seconds);
+        };
+
+        if self.parallel {
+            if !gravity.is_zero() {
+                velocity::par_for_each(&mut velocities, apply_gravity);
+            }
+            velocity::par_for_each((&mut velocities, &accelerations), accelerate);
+        } else {
+            if !gravity.is_zero() {
+                (&mut velocities).join().for_each(apply_gravity);
+            }
+            (&mut velocities, &accelerations)
+                .join()
+                .for_each(accelerate);
+        }
+    }
+}
+
+/// Speeds a ball up by an acceleration over `delta_seconds`.
+pub fn accelerate_ball(velocity: &mut Velocity, x: f32, y: f32, delta_seconds: f32) {
+    velocity.x += x * delta_seconds;
+    velocity.y += y * delta_seconds;
+}
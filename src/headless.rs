@@ -0,0 +1,132 @@
+//! Running the simulation without a window.
+
+use amethyst::{core::timing::Time, GameData, SimpleState, SimpleTrans, StateData, Trans};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use std::{path::PathBuf, time::Instant};
+
+use crate::{
+    apply_action, benchmark,
+    ramp::Ramp,
+    replay::{Replay, ReplayStep},
+    snapshot,
+    snapshot::Snapshot,
+    spawn_balls, Scenario,
+};
+
+/// Settings for a run without a window, loaded from `resources/headless_config.ron`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HeadlessConfig {
+    /// Size of the arena the balls bounce around in, unless the scenario gives one.
+    pub dimensions: (u32, u32),
+    /// Number of frames to simulate before quitting.
+    pub frames: u64,
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        Self {
+            dimensions: (500, 500),
+            frames: 1000,
+        }
+    }
+}
+
+/// Spawns the balls without sprites or a camera, or restores them from a snapshot, then quits
+/// after a fixed number of frames, when the ramp is done if there is one, or at the end of the
+/// recording being played back.
+pub struct HeadlessState {
+    frames: u64,
+    started: Option<Instant>,
+    ramp: Option<Ramp>,
+    snapshot: Option<Snapshot>,
+    /// Where to save a snapshot when the run ends.
+    save_snapshot: Option<PathBuf>,
+    replay: Option<Replay>,
+}
+
+impl HeadlessState {
+    /// Runs for `frames` frames, ramping up with `ramp` if given one, starting from `snapshot`
+    /// instead of spawning if given one, saving a snapshot to `save_snapshot` when it ends and
+    /// recording or playing back `replay`.
+    pub fn new(
+        frames: u64,
+        ramp: Option<Ramp>,
+        snapshot: Option<Snapshot>,
+        save_snapshot: Option<PathBuf>,
+        replay: Option<Replay>,
+    ) -> Self {
+        Self {
+            frames,
+            started: None,
+            ramp,
+            snapshot,
+            save_snapshot,
+            replay,
+        }
+    }
+}
+
+impl SimpleState for HeadlessState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        match self.snapshot.take() {
+            Some(snapshot) => snapshot.restore(data.world, None),
+            None => {
+                let ball_count = data.world.read_resource::<Scenario>().ball_count;
+                spawn_balls(data.world, ball_count, None);
+            }
+        }
+
+        self.started = Some(Instant::now());
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if let Some(replay) = &mut self.replay {
+            let step = replay.update(data.world, |world, action| {
+                apply_action(world, action, None)
+            });
+
+            match step {
+                ReplayStep::Recorded => {}
+                ReplayStep::Played => return Trans::None,
+                ReplayStep::Finished => return Trans::Quit,
+            }
+        }
+
+        if let Some(ramp) = &mut self.ramp {
+            return ramp.advance(data.world, None);
+        }
+
+        if data.world.read_resource::<Time>().frame_number() >= self.frames {
+            Trans::Quit
+        } else {
+            Trans::None
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(started) = self.started {
+            let elapsed = started.elapsed();
+            let frames = data.world.read_resource::<Time>().frame_number();
+
+            info!(
+                "Simulated {} frames in {:?} ({:.1} frames per second)",
+                frames,
+                elapsed,
+                frames as f64 / elapsed.as_secs_f64()
+            );
+        }
+
+        benchmark::finish(data.world);
+
+        if let Some(replay) = &mut self.replay {
+            replay.finish(data.world);
+        }
+
+        if let Some(path) = &self.save_snapshot {
+            snapshot::save(data.world, path);
+        }
+    }
+}
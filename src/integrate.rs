@@ -0,0 +1,136 @@
+//! Accelerating, moving and bouncing the balls in a single pass.
+
+use amethyst::{
+    core::timing::Time,
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+};
+
+use std::marker::PhantomData;
+
+use crate::{
+    arena::ArenaBounds,
+    bounce::bounce_ball,
+    boundary::Boundaries,
+    extent::Extent,
+    forces::{accelerate_ball, Acceleration, Gravity},
+    material::BounceMaterial,
+    movement::move_ball,
+    position::Position,
+    timestep::FixedTimestep,
+    velocity::{self, Velocity},
+};
+
+/// Accelerates, moves and bounces every ball in a single pass, instead of walking the storages
+/// once each in `AccelerationSystem`, `MovementSystem` and `BounceSystem`. If there is a
+/// `FixedTimestep` resource, this happens once per due step instead of once by the frame time.
+pub struct IntegrateBounceSystem<P> {
+    parallel: bool,
+    marker: PhantomData<P>,
+}
+
+impl<P> IntegrateBounceSystem<P> {
+    /// Runs the pass on the dispatcher's thread pool when `parallel` is set.
+    pub fn new(parallel: bool) -> Self {
+        Self {
+            parallel,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, P: Position> System<'s> for IntegrateBounceSystem<P> {
+    type SystemData = (
+        Entities<'s>,
+        ReadExpect<'s, ArenaBounds>,
+        Read<'s, Boundaries>,
+        Read<'s, BounceMaterial>,
+        ReadStorage<'s, BounceMaterial>,
+        ReadStorage<'s, Extent>,
+        Read<'s, Gravity>,
+        ReadStorage<'s, Acceleration>,
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, P>,
+        Read<'s, Time>,
+        Option<Read<'s, FixedTimestep>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            arena,
+            boundaries,
+            wall_material,
+            materials,
+            extents,
+            gravity,
+            accelerations,
+            mut velocities,
+            mut positions,
+            time,
+            fixed_timestep,
+        ): Self::SystemData,
+    ) {
+        let arena: &ArenaBounds = &arena;
+        let boundaries: &Boundaries = &boundaries;
+        let wall_material: &BounceMaterial = &wall_material;
+        let gravity: &Gravity = &gravity;
+        let (delta_seconds, steps) = match fixed_timestep {
+            Some(fixed_timestep) => (fixed_timestep.step_seconds(), fixed_timestep.substeps()),
+            None => (time.delta_seconds(), 1),
+        };
+
+        // Returns the entity if a wall absorbed it, skipping the steps left over.
+        let integrate_and_bounce =
+            |(position, (entity, velocity, material, extent, acceleration)): (
+                &mut P,
+                (
+                    Entity,
+                    &mut Velocity,
+                    Option<&BounceMaterial>,
+                    Option<&Extent>,
+                    Option<&Acceleration>,
+                ),
+            )| {
+                let material = material.unwrap_or(wall_material);
+                let (acceleration_x, acceleration_y) = match acceleration {
+                    Some(acceleration) => (gravity.x + acceleration.x, gravity.y + acceleration.y),
+                    None => (gravity.x, gravity.y),
+                };
+
+                for _ in 0..steps {
+                    accelerate_ball(velocity, acceleration_x, acceleration_y, delta_seconds);
+                    move_ball(position, velocity, delta_seconds);
+
+                    if bounce_ball(position, velocity, extent, arena, boundaries, material) {
+                        return Some(entity);
+                    }
+                }
+
+                None
+            };
+
+        let rest = (
+            &*entities,
+            &mut velocities,
+            materials.maybe(),
+            extents.maybe(),
+            accelerations.maybe(),
+        );
+
+        let absorbed = if self.parallel {
+            velocity::par_filter_map(&mut positions, rest, integrate_and_bounce)
+        } else {
+            (&mut positions, rest)
+                .join()
+                .filter_map(integrate_and_bounce)
+                .collect::<Vec<_>>()
+        };
+
+        for entity in absorbed {
+            entities
+                .delete(entity)
+                .expect("the entity was just joined, so it is alive");
+        }
+    }
+}
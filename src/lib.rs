@@ -0,0 +1,48 @@
+//! Balls bouncing around an arena, as systems and states for an amethyst game.
+//!
+//! `BounceBundle` adds the systems that move the balls, bounce them off the walls of the
+//! `ArenaBounds` and, if asked, make them collide. `State` runs a windowed game with them and
+//! `HeadlessState` runs one without a window, both spawning balls as the `Scenario` describes.
+//! Balls can also be spawned into any `World` with `spawn_balls` or `create_ball`.
+
+#![warn(missing_docs)]
+
+pub mod arena;
+pub mod benchmark;
+pub mod bounce;
+pub mod boundary;
+pub mod bundle;
+pub mod collision;
+pub mod extent;
+pub mod forces;
+pub mod headless;
+pub mod integrate;
+pub mod material;
+pub mod movement;
+pub mod position;
+pub mod ramp;
+pub mod random;
+pub mod replay;
+pub mod scenario;
+pub mod snapshot;
+pub mod spawn;
+pub mod state;
+pub mod stats;
+pub mod timestep;
+pub mod timing;
+pub mod velocity;
+pub mod window;
+
+pub use crate::{
+    arena::ArenaBounds,
+    bounce::BounceSystem,
+    bundle::BounceBundle,
+    headless::HeadlessState,
+    integrate::IntegrateBounceSystem,
+    movement::MovementSystem,
+    scenario::Scenario,
+    spawn::{apply_action, ball_count, create_ball, despawn_balls, spawn_balls},
+    state::State,
+    velocity::{Velocity, VELOCITY_STORAGE},
+    window::WindowResizeSystem,
+};
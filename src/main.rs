@@ -1,53 +1,149 @@
 use amethyst::{
-    assets::{AssetStorage, Loader},
-    core::{
-        bundle::SystemBundle,
-        frame_limiter::FrameRateLimitStrategy,
-        timing::Time,
-        transform::{Transform, TransformBundle},
-    },
-    ecs::{
-        prelude::DispatcherBuilder, Component, DenseVecStorage, Join, Read, ReadExpect,
-        ReadStorage, System, WriteStorage,
-    },
-    error::Error,
-    prelude::{Builder, GameDataBuilder, World},
+    core::{frame_limiter::FrameRateLimitStrategy, transform::TransformBundle},
+    input::{InputBundle, StringBindings},
+    prelude::GameDataBuilder,
     renderer::{
-        camera::{Camera, Projection},
-        formats::texture::ImageFormat,
         plugins::{RenderFlat2D, RenderToWindow},
-        sprite::{SpriteRender, SpriteSheet, SpriteSheetFormat, SpriteSheetHandle},
         types::DefaultBackend,
-        RenderingBundle, Texture,
+        RenderingBundle,
     },
+    ui::{RenderUi, UiBundle},
     utils::application_root_dir,
-    window::ScreenDimensions,
-    Application, GameData, SimpleState, StateData,
+    Application,
+};
+
+use amethyst::config::Config;
+use log::info;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bounce::{
+    arena::ArenaBounds,
+    benchmark::BenchmarkBundle,
+    headless::{HeadlessConfig, HeadlessState},
+    position::PositionMode,
+    ramp::{Ramp, RampConfig},
+    random::SimulationRng,
+    replay::{Recording, Replay},
+    scenario::Scenario,
+    snapshot::Snapshot,
+    stats::StatsBundle,
+    timing::{DispatchEndSystem, DispatchStartSystem},
+    BounceBundle, State,
 };
 
-use amethyst::prelude::WorldExt;
-use rand::Rng;
-use std::time::Duration;
+mod options;
+
+use crate::options::Options;
 
 fn main() -> amethyst::Result<()> {
     amethyst::start_logger(Default::default());
 
+    let options = Options::from_args()?;
+
     let root = application_root_dir()?;
 
-    let config_path = root.join("resources").join("display_config.ron");
+    let scenario_path = options
+        .scenario
+        .clone()
+        .unwrap_or_else(|| root.join("resources").join("scenario.ron"));
+    let recording = match &options.playback {
+        Some(path) => Some(Recording::load_no_fallback(path)?),
+        None => None,
+    };
 
-    let game_data = GameDataBuilder::default()
-        .with_bundle(BounceBundle)?
+    // A recording is played back with the scenario and seed it was made with.
+    let scenario = match &recording {
+        Some(recording) => recording.scenario.clone(),
+        None => Scenario::load_no_fallback(&scenario_path)?,
+    };
+
+    let snapshot = match &options.snapshot {
+        Some(path) => Some(Snapshot::load_no_fallback(path)?),
+        None => None,
+    };
+
+    // A snapshot carries on with the generator where it was saved.
+    let rng = match &snapshot {
+        Some(snapshot) => {
+            info!(
+                "Starting from a snapshot of {} balls spawned with seed {}",
+                snapshot.balls.len(),
+                snapshot.rng.seed()
+            );
+            snapshot.rng.clone()
+        }
+        None => {
+            let seed = match &recording {
+                Some(recording) => recording.seed,
+                None => options.seed.or(scenario.seed).unwrap_or_else(rand::random),
+            };
+            info!("Spawning with seed {}", seed);
+            SimulationRng::from_seed(seed)
+        }
+    };
+
+    let replay = match (recording, &options.record) {
+        (Some(recording), _) => Some(Replay::playback(recording)),
+        (None, Some(path)) => Some(Replay::record(path.clone(), rng.seed(), scenario.clone())),
+        (None, None) => None,
+    };
+
+    if options.headless {
+        run_headless(root, &options, scenario, rng, snapshot, replay)
+    } else {
+        run_windowed(root, &options, scenario, rng, snapshot, replay)
+    }
+}
+
+fn run_windowed(
+    root: PathBuf,
+    options: &Options,
+    scenario: Scenario,
+    rng: SimulationRng,
+    snapshot: Option<Snapshot>,
+    replay: Option<Replay>,
+) -> amethyst::Result<()> {
+    let config_path = root.join("resources").join("display_config.ron");
+    let bindings_path = root.join("resources").join("bindings.ron");
+
+    let game_data = start_dispatch_timer(GameDataBuilder::default(), options)
+        .with_bundle(bounce_bundle(
+            options,
+            &scenario,
+            arena(&scenario, snapshot.as_ref()),
+        ))?
         .with_bundle(TransformBundle::new())?
+        .with_bundle(InputBundle::<StringBindings>::new().with_bindings_from_file(bindings_path)?)?
+        .with_bundle(UiBundle::<StringBindings>::new())?
         .with_bundle(
             RenderingBundle::<DefaultBackend>::new()
                 .with_plugin(
                     RenderToWindow::from_config_path(config_path).with_clear([0.0, 0.0, 0.0, 1.0]),
                 )
-                .with_plugin(RenderFlat2D::default()),
+                .with_plugin(RenderFlat2D::default())
+                .with_plugin(RenderUi::default()),
         )?;
 
-    let mut game = Application::build(root, State)?
+    // The timings are read once the dispatch timer stops, after rendering, so the overlay is
+    // drawn a frame late.
+    let game_data = with_benchmark(
+        stop_dispatch_timer(game_data, options).with_bundle(StatsBundle)?,
+        options,
+    )?;
+
+    let ramp = load_ramp(&root, options);
+
+    let state = State::new(ramp, snapshot, options.save_snapshot.clone(), replay);
+
+    let mut game = Application::build(root, state)?
+        .with_resource(scenario.wall_material.clone())
+        .with_resource(scenario.boundaries.clone())
+        .with_resource(scenario.gravity.clone())
+        .with_resource(scenario)
+        .with_resource(rng)
         .with_frame_limit(
             FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(2)),
             144,
@@ -59,199 +155,125 @@ fn main() -> amethyst::Result<()> {
     Ok(())
 }
 
-struct BounceBundle;
-
-impl<'a, 'b> SystemBundle<'a, 'b> for BounceBundle {
-    fn build(
-        self,
-        _world: &mut World,
-        builder: &mut DispatcherBuilder<'a, 'b>,
-    ) -> Result<(), Error> {
-        builder.add(WindowResizeSystem::new(), "window_resize_system", &[]);
-        builder.add(MovementSystem, "movement_system", &[]);
-        builder.add(BounceSystem, "bounce_system", &[]);
-
-        Ok(())
-    }
-}
-
-struct State;
-
-impl SimpleState for State {
-    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
-        let world = data.world;
-
-        let mut camera_transform = Transform::default();
-        camera_transform.set_translation_z(1.0);
-
-        let (width, height) = get_dimensions(world);
-
-        world
-            .create_entity()
-            .with(Camera::from(Projection::orthographic(
-                0., width, 0., -height, 0.1, 2000.0,
-            )))
-            .with(camera_transform)
-            .build();
-
-        let sprite_sheet_handle = load_sprite_sheet(world);
-
-        let mut rng = rand::thread_rng();
-
-        for _ in 0..100_000 {
-            let mut ball_transform = Transform::default();
-            let x = width / 2.0;
-            let y = height / 2.0;
+fn run_headless(
+    root: PathBuf,
+    options: &Options,
+    scenario: Scenario,
+    rng: SimulationRng,
+    snapshot: Option<Snapshot>,
+    replay: Option<Replay>,
+) -> amethyst::Result<()> {
+    let config = HeadlessConfig::load(root.join("resources").join("headless_config.ron"));
+    let (width, height) = config.dimensions;
+    let arena = arena(&scenario, snapshot.as_ref())
+        .unwrap_or_else(|| ArenaBounds::new(width as f32, height as f32));
+
+    let game_data = start_dispatch_timer(GameDataBuilder::default(), options)
+        .with_bundle(bounce_bundle(options, &scenario, Some(arena)).headless())?;
+
+    // Headless balls moved with a `Position2D` have no `Transform` for the transform systems.
+    let game_data = match options.position_mode() {
+        PositionMode::Transform => game_data.with_bundle(TransformBundle::new())?,
+        PositionMode::Position2D => game_data,
+    };
+    let game_data = with_benchmark(stop_dispatch_timer(game_data, options), options)?;
 
-            ball_transform.set_translation_xyz(x, y, 0.);
+    let ramp = load_ramp(&root, options);
 
-            let sprite_sheet = SpriteRender {
-                sprite_sheet: sprite_sheet_handle.clone(),
-                sprite_number: 0,
-            };
+    let state = HeadlessState::new(
+        config.frames,
+        ramp,
+        snapshot,
+        options.save_snapshot.clone(),
+        replay,
+    );
 
-            let range = 50.0;
-
-            world
-                .create_entity()
-                .with(sprite_sheet)
-                .with(Velocity {
-                    x: rng.gen_range(-range, range),
-                    y: rng.gen_range(-range, range),
-                })
-                .with(ball_transform)
-                .build();
-        }
-    }
-}
+    let mut game = Application::build(root, state)?
+        .with_resource(scenario.wall_material.clone())
+        .with_resource(scenario.boundaries.clone())
+        .with_resource(scenario.gravity.clone())
+        .with_resource(scenario)
+        .with_resource(rng)
+        .with_frame_limit(FrameRateLimitStrategy::Unlimited, 0)
+        .build(game_data)?;
 
-fn get_dimensions(world: &mut World) -> (f32, f32) {
-    let screen_dimensions = world.read_resource::<ScreenDimensions>();
+    game.run();
 
-    (screen_dimensions.width(), screen_dimensions.height())
+    Ok(())
 }
 
-fn load_sprite_sheet(world: &mut World) -> SpriteSheetHandle {
-    let texture_handle = {
-        let loader = world.read_resource::<Loader>();
-        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
-
-        loader.load(
-            "assets/spritesheet.png",
-            ImageFormat::default(),
-            (),
-            &texture_storage,
+/// The simulation systems, set up as the options and scenario ask.
+fn bounce_bundle(
+    options: &Options,
+    scenario: &Scenario,
+    arena: Option<ArenaBounds>,
+) -> BounceBundle {
+    BounceBundle::new()
+        .with_arena(arena)
+        .with_parallel(options.parallel)
+        .with_fused(options.fused)
+        .with_fixed_timestep(options.fixed_timestep())
+        .with_collisions(
+            scenario
+                .collisions
+                .as_ref()
+                .map(|collisions| collisions.broadphase),
         )
-    };
-
-    let loader = world.read_resource::<Loader>();
-    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
-
-    loader.load(
-        "resources/spritesheet.ron",
-        SpriteSheetFormat(texture_handle),
-        (),
-        &sprite_sheet_store,
-    )
+        .with_position_mode(options.position_mode())
+        .with_timings(options.time_systems)
 }
 
-pub struct MovementSystem;
-
-impl<'s> System<'s> for MovementSystem {
-    type SystemData = (
-        WriteStorage<'s, Transform>,
-        ReadStorage<'s, Velocity>,
-        Read<'s, Time>,
-    );
-
-    fn run(&mut self, (mut transforms, velocities, time): Self::SystemData) {
-        for (transform, velocity) in (&mut transforms, &velocities).join() {
-            let transform: &mut Transform = transform;
-            let velocity: &Velocity = velocity;
-            let delta_seconds = time.delta_seconds();
-
-            transform.prepend_translation_x(velocity.x * delta_seconds);
-            transform.prepend_translation_y(velocity.y * delta_seconds);
-        }
-    }
+/// The arena saved in the snapshot, or else the scenario's.
+fn arena(scenario: &Scenario, snapshot: Option<&Snapshot>) -> Option<ArenaBounds> {
+    snapshot
+        .map(|snapshot| snapshot.arena.clone())
+        .or_else(|| scenario.arena.clone())
 }
 
-struct WindowResizeSystem {
-    last_dimensions: ScreenDimensions,
+fn load_ramp(root: &Path, options: &Options) -> Option<Ramp> {
+    if options.ramp {
+        let config = RampConfig::load(root.join("resources").join("ramp_config.ron"));
+        Some(Ramp::new(config))
+    } else {
+        None
+    }
 }
 
-impl WindowResizeSystem {
-    pub fn new() -> Self {
-        Self {
-            last_dimensions: ScreenDimensions::new(0, 0, 0.0),
-        }
+/// Starts timing the dispatch ahead of every other system, when that was asked for.
+fn start_dispatch_timer<'a, 'b>(
+    game_data: GameDataBuilder<'a, 'b>,
+    options: &Options,
+) -> GameDataBuilder<'a, 'b> {
+    if options.time_dispatch {
+        game_data
+            .with(DispatchStartSystem, "dispatch_start_system", &[])
+            .with_barrier()
+    } else {
+        game_data
     }
 }
 
-impl<'s> System<'s> for WindowResizeSystem {
-    type SystemData = (ReadExpect<'s, ScreenDimensions>, WriteStorage<'s, Camera>);
-
-    fn run(&mut self, (screen_dimensions, mut cameras): Self::SystemData) {
-        if self.last_dimensions != *screen_dimensions {
-            for camera in (&mut cameras).join() {
-                if let Some(ortho) = camera.projection_mut().as_orthographic_mut() {
-                    ortho.set_bottom_and_top(0., -screen_dimensions.height());
-                    ortho.set_left_and_right(0., screen_dimensions.width());
-                }
-            }
-
-            self.last_dimensions = screen_dimensions.clone();
-        }
+/// Stops timing the dispatch once every system added so far has run, when that was asked for.
+fn stop_dispatch_timer<'a, 'b>(
+    game_data: GameDataBuilder<'a, 'b>,
+    options: &Options,
+) -> GameDataBuilder<'a, 'b> {
+    if options.time_dispatch {
+        game_data.with_thread_local(DispatchEndSystem)
+    } else {
+        game_data
     }
 }
 
-struct BounceSystem;
-
-impl<'s> System<'s> for BounceSystem {
-    type SystemData = (
-        ReadExpect<'s, ScreenDimensions>,
-        WriteStorage<'s, Velocity>,
-        WriteStorage<'s, Transform>,
-    );
-
-    fn run(&mut self, (screen, mut velocities, mut transforms): Self::SystemData) {
-        for (mut velocity, transform) in (&mut velocities, &mut transforms).join() {
-            let transform: &mut Transform = transform;
-
-            let current_y = transform.translation().y;
-            let current_x = transform.translation().x;
-
-            let height = screen.height();
-            let width = screen.width();
-
-            if current_y >= height {
-                transform.set_translation_y(height - 1.0);
-                velocity.y = -velocity.y;
-            }
-
-            if current_y <= 0.0 {
-                transform.set_translation_y(0.0);
-                velocity.y = -velocity.y;
-            }
-
-            if current_x >= width {
-                transform.set_translation_x(width - 1.0);
-                velocity.x = -velocity.x;
-            }
-
-            if current_x <= 0.0 {
-                transform.set_translation_x(0.0);
-                velocity.x = -velocity.x;
-            }
+/// Adds frame time recording when a benchmark report was asked for.
+fn with_benchmark<'a, 'b>(
+    game_data: GameDataBuilder<'a, 'b>,
+    options: &Options,
+) -> amethyst::Result<GameDataBuilder<'a, 'b>> {
+    match &options.benchmark {
+        Some(output) => {
+            game_data.with_bundle(BenchmarkBundle::new(output.clone(), options.warmup_frames))
         }
+        None => Ok(game_data),
     }
 }
-
-pub struct Velocity {
-    pub x: f32,
-    pub y: f32,
-}
-
-impl Component for Velocity {
-    type Storage = DenseVecStorage<Self>;
-}
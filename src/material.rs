@@ -0,0 +1,38 @@
+//! How much speed the balls keep when they bounce.
+
+use amethyst::ecs::{Component, DenseVecStorage};
+use serde::{Deserialize, Serialize};
+
+/// How much speed a ball keeps when it hits a wall.
+///
+/// As a resource it applies to every ball; as a component it overrides the resource for that
+/// ball alone.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BounceMaterial {
+    /// Fraction of the speed into the wall that comes back out of it. `1.0` is a perfect bounce.
+    pub restitution: f32,
+    /// Fraction of the speed along the wall lost on each hit. `0.0` is a frictionless wall.
+    pub friction: f32,
+}
+
+impl BounceMaterial {
+    /// Bounces a velocity off a wall, given its component across the wall and along it.
+    pub fn reflect(&self, normal: &mut f32, tangent: &mut f32) {
+        *normal = -*normal * self.restitution;
+        *tangent *= 1.0 - self.friction;
+    }
+}
+
+impl Default for BounceMaterial {
+    fn default() -> Self {
+        Self {
+            restitution: 1.0,
+            friction: 0.0,
+        }
+    }
+}
+
+impl Component for BounceMaterial {
+    type Storage = DenseVecStorage<Self>;
+}
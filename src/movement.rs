@@ -0,0 +1,56 @@
+//! Moving the balls along their velocity.
+
+use amethyst::{
+    core::timing::Time,
+    ecs::{Join, Read, ReadStorage, System, WriteStorage},
+};
+
+use std::marker::PhantomData;
+
+use crate::{position::Position, velocity::Velocity};
+
+/// Moves every ball's position `P` along its velocity. When `parallel` is set, the work is spread
+/// over the dispatcher's thread pool with `Position::par_for_each`.
+pub struct MovementSystem<P> {
+    parallel: bool,
+    marker: PhantomData<P>,
+}
+
+impl<P> MovementSystem<P> {
+    /// Moves the balls on the dispatcher's thread pool when `parallel` is set.
+    pub fn new(parallel: bool) -> Self {
+        Self {
+            parallel,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, P: Position> System<'s> for MovementSystem<P> {
+    type SystemData = (
+        WriteStorage<'s, P>,
+        ReadStorage<'s, Velocity>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (mut positions, velocities, time): Self::SystemData) {
+        let delta_seconds = time.delta_seconds();
+
+        if self.parallel {
+            P::par_for_each(&mut positions, &velocities, |(position, velocity)| {
+                move_ball(position, velocity, delta_seconds)
+            });
+        } else {
+            for (position, velocity) in (&mut positions, &velocities).join() {
+                move_ball(position, velocity, delta_seconds);
+            }
+        }
+    }
+}
+
+/// Moves a ball along its velocity for `delta_seconds`.
+pub fn move_ball<P: Position>(position: &mut P, velocity: &Velocity, delta_seconds: f32) {
+    let [x, y] = position.xy();
+    position.set_x(x + velocity.x * delta_seconds);
+    position.set_y(y + velocity.y * delta_seconds);
+}
@@ -0,0 +1,148 @@
+use amethyst::error::Error;
+
+use std::{env, path::PathBuf};
+
+use bounce::{position::PositionMode, timestep::FixedTimestep};
+
+/// Settings chosen on the command line.
+#[derive(Debug)]
+pub struct Options {
+    /// Run the simulation without a window or any rendering.
+    pub headless: bool,
+    /// Scenario file to load instead of `resources/scenario.ron`.
+    pub scenario: Option<PathBuf>,
+    /// Seed for spawning, overriding the scenario's.
+    pub seed: Option<u64>,
+    /// Where to write a benchmark report when the run ends.
+    pub benchmark: Option<PathBuf>,
+    /// Frames to skip before benchmark measurements start.
+    pub warmup_frames: u64,
+    /// Keep adding balls until the frame time budget in `resources/ramp_config.ron` is missed.
+    pub ramp: bool,
+    /// Spread movement and bouncing across threads.
+    pub parallel: bool,
+    /// Move and bounce the balls in one pass instead of two.
+    pub fused: bool,
+    /// Move the balls with a `Position2D` instead of their `Transform`.
+    pub position_2d: bool,
+    /// Time every simulation system, for the stats overlay and the benchmark report.
+    pub time_systems: bool,
+    /// Time the whole dispatch, for the stats overlay and the benchmark report.
+    pub time_dispatch: bool,
+    /// Advance the simulation in steps of this many seconds instead of by the frame time.
+    pub fixed_step: Option<f32>,
+    /// Most fixed steps run in one frame.
+    pub max_substeps: u32,
+    /// Snapshot to start from instead of spawning the scenario's balls.
+    pub snapshot: Option<PathBuf>,
+    /// Where to save a snapshot when the run ends, and when one is asked for while it runs.
+    pub save_snapshot: Option<PathBuf>,
+    /// Where to write a recording of the run.
+    pub record: Option<PathBuf>,
+    /// Recording to play back, checking the run goes the same way.
+    pub playback: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            scenario: None,
+            seed: None,
+            benchmark: None,
+            warmup_frames: 120,
+            ramp: false,
+            parallel: false,
+            fused: false,
+            position_2d: false,
+            time_systems: false,
+            time_dispatch: false,
+            fixed_step: None,
+            max_substeps: 8,
+            snapshot: None,
+            save_snapshot: None,
+            record: None,
+            playback: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn from_args() -> Result<Self, Error> {
+        let mut options = Options::default();
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--scenario" => options.scenario = Some(value(&arg, args.next())?.into()),
+                "--seed" => options.seed = Some(value(&arg, args.next())?.parse()?),
+                "--benchmark" => options.benchmark = Some(value(&arg, args.next())?.into()),
+                "--warmup" => options.warmup_frames = value(&arg, args.next())?.parse()?,
+                "--ramp" => options.ramp = true,
+                "--parallel" => options.parallel = true,
+                "--fused" => options.fused = true,
+                "--position-2d" => options.position_2d = true,
+                "--time-systems" => options.time_systems = true,
+                "--time-dispatch" => options.time_dispatch = true,
+                "--fixed-step" => {
+                    let step: f32 = value(&arg, args.next())?.parse()?;
+                    // NaN passes `step <= 0.0`, and NaN or infinite steps never run.
+                    if !step.is_finite() || step <= 0.0 {
+                        return Err(Error::from_string(
+                            "`--fixed-step` must be a positive number",
+                        ));
+                    }
+                    options.fixed_step = Some(step);
+                }
+                "--max-substeps" => {
+                    let max_substeps = value(&arg, args.next())?.parse()?;
+                    // With no substeps allowed, every due step is dropped and the balls freeze.
+                    if max_substeps == 0 {
+                        return Err(Error::from_string("`--max-substeps` must be at least 1"));
+                    }
+                    options.max_substeps = max_substeps;
+                }
+                "--snapshot" => options.snapshot = Some(value(&arg, args.next())?.into()),
+                "--save-snapshot" => options.save_snapshot = Some(value(&arg, args.next())?.into()),
+                "--record" => options.record = Some(value(&arg, args.next())?.into()),
+                "--playback" => options.playback = Some(value(&arg, args.next())?.into()),
+                other => return Err(Error::from_string(format!("unknown argument `{}`", other))),
+            }
+        }
+
+        if options.record.is_some() && options.playback.is_some() {
+            return Err(Error::from_string(
+                "`--record` and `--playback` can't be used together",
+            ));
+        }
+
+        // Neither the ramp's real frame times nor a snapshot's balls make it into a recording.
+        if (options.record.is_some() || options.playback.is_some())
+            && (options.ramp || options.snapshot.is_some())
+        {
+            return Err(Error::from_string(
+                "recordings can't be made or played back with `--ramp` or `--snapshot`",
+            ));
+        }
+
+        Ok(options)
+    }
+
+    pub fn position_mode(&self) -> PositionMode {
+        if self.position_2d {
+            PositionMode::Position2D
+        } else {
+            PositionMode::Transform
+        }
+    }
+
+    pub fn fixed_timestep(&self) -> Option<FixedTimestep> {
+        self.fixed_step
+            .map(|step| FixedTimestep::new(step, self.max_substeps))
+    }
+}
+
+fn value(arg: &str, value: Option<String>) -> Result<String, Error> {
+    value.ok_or_else(|| Error::from_string(format!("`{}` needs a value", arg)))
+}
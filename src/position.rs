@@ -0,0 +1,209 @@
+//! The components the balls can be moved with.
+
+use amethyst::{
+    core::transform::Transform,
+    ecs::{
+        rayon::prelude::{IntoParallelIterator, ParallelIterator},
+        Component, DenseVecStorage, Join, ParJoin, ReadStorage, System, WriteStorage,
+    },
+};
+
+/// Where a ball is, for the systems that move and bounce it. Implemented by `Transform` and by
+/// the much smaller `Position2D`, so the systems can run on either.
+pub trait Position: Component + Send + Sync {
+    /// Where the ball is.
+    fn xy(&self) -> [f32; 2];
+
+    /// Moves the ball along x.
+    fn set_x(&mut self, x: f32);
+
+    /// Moves the ball along y.
+    fn set_y(&mut self, y: f32);
+
+    /// Scale the ball's sprite is drawn at.
+    fn scale(&self) -> [f32; 2];
+
+    /// Calls `f` on every ball's position joined with `rest`, spread over the thread pool, and
+    /// collects what it returns. Uses `par_join` where the position's storage allows it.
+    fn par_filter_map<'a, R, F, T>(
+        positions: &'a mut WriteStorage<'_, Self>,
+        rest: R,
+        f: F,
+    ) -> Vec<T>
+    where
+        R: Join + ParJoin + Send,
+        R::Mask: Send + Sync,
+        R::Type: Send,
+        R::Value: Send,
+        F: Fn((&'a mut Self, R::Type)) -> Option<T> + Send + Sync,
+        T: Send;
+
+    /// Calls `f` on every ball's position joined with `rest`, spread over the thread pool.
+    fn par_for_each<'a, R, F>(positions: &'a mut WriteStorage<'_, Self>, rest: R, f: F)
+    where
+        R: Join + ParJoin + Send,
+        R::Mask: Send + Sync,
+        R::Type: Send,
+        R::Value: Send,
+        F: Fn((&'a mut Self, R::Type)) + Send + Sync,
+    {
+        Self::par_filter_map(positions, rest, |ball| {
+            f(ball);
+            None::<()>
+        });
+    }
+}
+
+/// Collects a join and hands its items to the thread pool, for storages that can't be
+/// `par_join`ed mutably. Joining sequentially also emits a `FlaggedStorage`'s modification events
+/// in order.
+pub fn collect_filter_map<J, F, T>(join: J, f: F) -> Vec<T>
+where
+    J: Join,
+    J::Type: Send,
+    F: Fn(J::Type) -> Option<T> + Send + Sync,
+    T: Send,
+{
+    join.join()
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .filter_map(f)
+        .collect()
+}
+
+impl Position for Transform {
+    fn xy(&self) -> [f32; 2] {
+        [self.translation().x, self.translation().y]
+    }
+
+    fn set_x(&mut self, x: f32) {
+        self.set_translation_x(x);
+    }
+
+    fn set_y(&mut self, y: f32) {
+        self.set_translation_y(y);
+    }
+
+    fn scale(&self) -> [f32; 2] {
+        let scale = Transform::scale(self);
+        [scale.x, scale.y]
+    }
+
+    // `Transform` lives in a `FlaggedStorage`, which specs won't `par_join` mutably.
+    fn par_filter_map<'a, R, F, T>(
+        positions: &'a mut WriteStorage<'_, Self>,
+        rest: R,
+        f: F,
+    ) -> Vec<T>
+    where
+        R: Join + ParJoin + Send,
+        R::Mask: Send + Sync,
+        R::Type: Send,
+        R::Value: Send,
+        F: Fn((&'a mut Self, R::Type)) -> Option<T> + Send + Sync,
+        T: Send,
+    {
+        collect_filter_map((positions, rest), f)
+    }
+}
+
+/// Just the position of a ball, without the rotation, scale and global matrix of a `Transform`.
+///
+/// Balls have one when the simulation runs on `Position2D` instead of `Transform`. Balls that are
+/// rendered keep a `Transform` as well, which `Position2DSyncSystem` keeps up to date.
+///
+/// Scaled sprites aren't supported: the `Transform`'s scale is ignored, and the walls treat every
+/// sprite as drawn at its natural size.
+#[derive(Clone, Debug)]
+pub struct Position2D {
+    /// Horizontal position.
+    pub x: f32,
+    /// Vertical position.
+    pub y: f32,
+}
+
+impl Position for Position2D {
+    fn xy(&self) -> [f32; 2] {
+        [self.x, self.y]
+    }
+
+    fn set_x(&mut self, x: f32) {
+        self.x = x;
+    }
+
+    fn set_y(&mut self, y: f32) {
+        self.y = y;
+    }
+
+    // Only the position is kept, so sprites are taken to be unscaled.
+    fn scale(&self) -> [f32; 2] {
+        [1.0, 1.0]
+    }
+
+    fn par_filter_map<'a, R, F, T>(
+        positions: &'a mut WriteStorage<'_, Self>,
+        rest: R,
+        f: F,
+    ) -> Vec<T>
+    where
+        R: Join + ParJoin + Send,
+        R::Mask: Send + Sync,
+        R::Type: Send,
+        R::Value: Send,
+        F: Fn((&'a mut Self, R::Type)) -> Option<T> + Send + Sync,
+        T: Send,
+    {
+        (positions, rest).par_join().filter_map(f).collect()
+    }
+}
+
+impl Component for Position2D {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Where a ball is, taken from its `Position2D` if it has one and its `Transform` otherwise.
+pub fn translation(
+    position: Option<&Position2D>,
+    transform: Option<&Transform>,
+) -> Option<[f32; 3]> {
+    let z = transform.map_or(0.0, |transform| transform.translation().z);
+
+    match (position, transform) {
+        (Some(position), _) => Some([position.x, position.y, z]),
+        (None, Some(transform)) => {
+            let translation = transform.translation();
+            Some([translation.x, translation.y, translation.z])
+        }
+        (None, None) => None,
+    }
+}
+
+/// Which component the simulation moves the balls with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PositionMode {
+    /// The balls' `Transform`, which every ball has anyway.
+    Transform,
+    /// A `Position2D`, with a `Transform` only on balls that are rendered.
+    Position2D,
+}
+
+impl Default for PositionMode {
+    fn default() -> Self {
+        PositionMode::Transform
+    }
+}
+
+/// Copies each `Position2D` into the ball's `Transform`, for rendering. Runs after the balls have
+/// moved, bounced and collided, and before the `TransformSystem`.
+pub struct Position2DSyncSystem;
+
+impl<'s> System<'s> for Position2DSyncSystem {
+    type SystemData = (ReadStorage<'s, Position2D>, WriteStorage<'s, Transform>);
+
+    fn run(&mut self, (positions, mut transforms): Self::SystemData) {
+        for (position, transform) in (&positions, &mut transforms).join() {
+            transform.set_translation_x(position.x);
+            transform.set_translation_y(position.y);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Adding balls until the frame time budget is missed.
+
+use amethyst::{
+    core::timing::Time, prelude::World, renderer::sprite::SpriteSheetHandle, SimpleTrans, Trans,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{ball_count, spawn_balls};
+
+/// Settings for finding the largest ball count that stays within a frame time budget, loaded
+/// from `resources/ramp_config.ron`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RampConfig {
+    /// Mean frame time, in milliseconds, that each ball count has to stay under.
+    pub budget_ms: f32,
+    /// Balls added each time the budget holds.
+    pub batch_size: usize,
+    /// Frames averaged to decide whether the budget holds.
+    pub window_frames: usize,
+    /// Frames ignored after a batch is added, so the cost of spawning it isn't measured.
+    pub settle_frames: usize,
+}
+
+impl Default for RampConfig {
+    fn default() -> Self {
+        Self {
+            budget_ms: 16.6,
+            batch_size: 5_000,
+            window_frames: 60,
+            settle_frames: 10,
+        }
+    }
+}
+
+/// What the ramp wants done after a frame.
+#[derive(Debug, PartialEq)]
+pub enum RampStep {
+    /// Keep measuring the current ball count.
+    Measure,
+    /// The budget held, so spawn this many more balls.
+    Spawn(usize),
+    /// The budget was missed and the ramp is over.
+    Done,
+}
+
+/// Adds batches of balls while the rolling frame time stays within budget.
+pub struct Ramp {
+    config: RampConfig,
+    frames_since_spawn: usize,
+    total_seconds: f32,
+    best: Option<usize>,
+}
+
+impl Ramp {
+    /// Starts ramping from the balls already alive.
+    pub fn new(config: RampConfig) -> Self {
+        Self {
+            config,
+            frames_since_spawn: 0,
+            total_seconds: 0.0,
+            best: None,
+        }
+    }
+
+    /// Measures the frame that just ran and spawns the next batch once the budget has held,
+    /// quitting when it is missed.
+    pub fn advance(
+        &mut self,
+        world: &mut World,
+        sprite_sheet_handle: Option<&SpriteSheetHandle>,
+    ) -> SimpleTrans {
+        let delta_seconds = world.read_resource::<Time>().delta_real_seconds();
+
+        match self.update(delta_seconds, || ball_count(world)) {
+            RampStep::Measure => Trans::None,
+            RampStep::Spawn(count) => {
+                spawn_balls(world, count, sprite_sheet_handle);
+                Trans::None
+            }
+            RampStep::Done => Trans::Quit,
+        }
+    }
+
+    /// Feeds in the time the last frame took. `count_balls` is only called once a window is
+    /// full, since walking every ball on the frames being measured would add to their time.
+    pub fn update<F>(&mut self, delta_seconds: f32, count_balls: F) -> RampStep
+    where
+        F: FnOnce() -> usize,
+    {
+        self.frames_since_spawn += 1;
+
+        if self.frames_since_spawn <= self.config.settle_frames {
+            return RampStep::Measure;
+        }
+
+        self.total_seconds += delta_seconds;
+
+        if self.frames_since_spawn < self.config.settle_frames + self.config.window_frames {
+            return RampStep::Measure;
+        }
+
+        let mean_ms = self.total_seconds / self.config.window_frames as f32 * 1000.0;
+        let ball_count = count_balls();
+
+        self.frames_since_spawn = 0;
+        self.total_seconds = 0.0;
+
+        if mean_ms <= self.config.budget_ms {
+            info!("{} balls held the budget at {:.2} ms", ball_count, mean_ms);
+
+            self.best = Some(ball_count);
+
+            RampStep::Spawn(self.config.batch_size)
+        } else {
+            info!(
+                "{} balls missed the budget at {:.2} ms",
+                ball_count, mean_ms
+            );
+
+            match self.best {
+                Some(best) => info!(
+                    "Largest ball count within {} ms: {}",
+                    self.config.budget_ms, best
+                ),
+                None => info!(
+                    "Even the starting ball count missed the {} ms budget",
+                    self.config.budget_ms
+                ),
+            }
+
+            RampStep::Done
+        }
+    }
+}
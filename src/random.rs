@@ -0,0 +1,46 @@
+//! The seeded random number generator that spawning draws from.
+
+use rand::{Error, RngCore, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+/// The random number generator all spawning code draws from. Two runs created with the same
+/// seed spawn identical balls, and a `Snapshot` carries its state on from where it was saved.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SimulationRng {
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl SimulationRng {
+    /// A generator that always draws the same numbers for the same seed.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Pcg32::seed_from_u64(seed),
+        }
+    }
+
+    /// The seed this generator was created from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
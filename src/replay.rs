@@ -0,0 +1,247 @@
+//! Recording runs and playing them back.
+
+use amethyst::{
+    config::Config,
+    core::{timing::Time, transform::Transform},
+    ecs::{Join, ReadStorage},
+    prelude::{World, WorldExt},
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use std::{mem, path::PathBuf};
+
+use crate::{
+    position::{self, Position2D},
+    Scenario, Velocity,
+};
+
+/// Everything needed to run a game again exactly as it went: how it was set up, how long each
+/// frame took and what the player did, with checksums to tell whether the re-run kept up.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Recording {
+    /// Seed the balls were spawned with.
+    pub seed: u64,
+    /// Scenario the game was set up with.
+    pub scenario: Scenario,
+    /// Every frame that ran, in order.
+    pub frames: Vec<RecordedFrame>,
+    /// Checksum of the balls once the last frame had run.
+    pub final_checksum: Option<u64>,
+}
+
+/// One frame of a `Recording`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordedFrame {
+    /// Time the simulation advanced by.
+    pub delta_seconds: f32,
+    /// Input actions handled just before the frame ran, in order.
+    pub actions: Vec<String>,
+    /// Checksum of the balls as the frame began, after its actions.
+    pub checksum: u64,
+}
+
+/// What happened to the frame about to run.
+#[derive(Debug, PartialEq)]
+pub enum ReplayStep {
+    /// It was added to the recording.
+    Recorded,
+    /// It was set up from the recording.
+    Played,
+    /// The recording has no frames left, so the playback is over.
+    Finished,
+}
+
+/// Records a game to a file, or plays a recording back and checks it goes the same way.
+pub enum Replay {
+    /// Adding every frame to a recording.
+    Record {
+        /// Where the recording is written.
+        path: PathBuf,
+        /// The frames so far.
+        recording: Recording,
+        /// Actions handled since the last frame.
+        actions: Vec<String>,
+    },
+    /// Setting every frame up from a recording.
+    Playback {
+        /// The recording being played back.
+        recording: Recording,
+        /// Index of the frame to set up next.
+        next_frame: usize,
+        /// The first frame whose checksum didn't match, if one hasn't.
+        diverged: Option<usize>,
+    },
+}
+
+impl Replay {
+    /// Starts a recording of a game set up with this seed and scenario, written to `path` when
+    /// the game stops.
+    pub fn record(path: PathBuf, seed: u64, scenario: Scenario) -> Self {
+        Replay::Record {
+            path,
+            recording: Recording {
+                seed,
+                scenario,
+                frames: Vec::new(),
+                final_checksum: None,
+            },
+            actions: Vec::new(),
+        }
+    }
+
+    /// Plays back a recording. The game has to be set up with its seed and scenario.
+    pub fn playback(recording: Recording) -> Self {
+        Replay::Playback {
+            recording,
+            next_frame: 0,
+            diverged: None,
+        }
+    }
+
+    /// Notes an input action that changes the simulation. Returns whether to apply it: while
+    /// recording it is applied and kept for the next frame, and while playing back it is ignored
+    /// in favour of the recorded actions.
+    pub fn input(&mut self, action: &str) -> bool {
+        match self {
+            Replay::Record { actions, .. } => {
+                actions.push(action.to_string());
+                true
+            }
+            Replay::Playback { .. } => false,
+        }
+    }
+
+    /// Records the frame about to run, or sets it up from the recording by applying its actions
+    /// with `apply` and replacing the frame time, then compares checksums. Called from the
+    /// state's `update`, before the systems run.
+    pub fn update<F>(&mut self, world: &mut World, mut apply: F) -> ReplayStep
+    where
+        F: FnMut(&mut World, &str),
+    {
+        match self {
+            Replay::Record {
+                recording, actions, ..
+            } => {
+                let delta_seconds = world.read_resource::<Time>().delta_seconds();
+
+                recording.frames.push(RecordedFrame {
+                    delta_seconds,
+                    actions: mem::take(actions),
+                    checksum: checksum(world),
+                });
+
+                ReplayStep::Recorded
+            }
+            Replay::Playback {
+                recording,
+                next_frame,
+                diverged,
+            } => {
+                let frame = match recording.frames.get(*next_frame) {
+                    Some(frame) => frame,
+                    None => {
+                        if diverged.is_none() && recording.final_checksum != Some(checksum(world)) {
+                            *diverged = Some(*next_frame);
+                        }
+
+                        return ReplayStep::Finished;
+                    }
+                };
+
+                for action in &frame.actions {
+                    apply(world, action);
+                }
+
+                world
+                    .write_resource::<Time>()
+                    .set_delta_seconds(frame.delta_seconds);
+
+                if diverged.is_none() && checksum(world) != frame.checksum {
+                    error!(
+                        "Playback diverged from the recording at frame {}",
+                        next_frame
+                    );
+                    *diverged = Some(*next_frame);
+                }
+
+                *next_frame += 1;
+
+                ReplayStep::Played
+            }
+        }
+    }
+
+    /// Writes the recording, or reports how the playback went. Called as the game stops.
+    pub fn finish(&mut self, world: &World) {
+        match self {
+            Replay::Record {
+                path, recording, ..
+            } => {
+                recording.final_checksum = Some(checksum(world));
+
+                match recording.write(path.as_path()) {
+                    Ok(()) => info!(
+                        "Recorded {} frames to {}",
+                        recording.frames.len(),
+                        path.display()
+                    ),
+                    Err(err) => error!("Failed to write recording: {}", err),
+                }
+            }
+            Replay::Playback {
+                recording,
+                next_frame,
+                diverged,
+            } => match diverged {
+                Some(frame) => error!(
+                    "Playback diverged from the recording at frame {} of {}",
+                    frame,
+                    recording.frames.len()
+                ),
+                None if *next_frame < recording.frames.len() => info!(
+                    "Playback stopped after {} of {} frames without diverging",
+                    next_frame,
+                    recording.frames.len()
+                ),
+                None => info!(
+                    "Playback matched the recording for all {} frames",
+                    recording.frames.len()
+                ),
+            },
+        }
+    }
+}
+
+/// FNV-1a hash of every ball's position and velocity, in entity order. Unlike the standard
+/// library's hasher, it is guaranteed to give the same result on every build.
+pub fn checksum(world: &World) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let (positions, transforms, velocities): (
+        ReadStorage<'_, Position2D>,
+        ReadStorage<'_, Transform>,
+        ReadStorage<'_, Velocity>,
+    ) = world.system_data();
+
+    let mut hash = OFFSET_BASIS;
+
+    for (position, transform, velocity) in
+        (positions.maybe(), transforms.maybe(), &velocities).join()
+    {
+        let [x, y, z] = match position::translation(position, transform) {
+            Some(translation) => translation,
+            None => continue,
+        };
+
+        for value in &[x, y, z, velocity.x, velocity.y] {
+            for byte in &value.to_bits().to_le_bytes() {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(PRIME);
+            }
+        }
+    }
+
+    hash
+}
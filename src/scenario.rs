@@ -0,0 +1,85 @@
+//! The workload a run spawns.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::ArenaBounds,
+    boundary::Boundaries,
+    collision::CollisionConfig,
+    forces::{Acceleration, Gravity},
+    material::BounceMaterial,
+};
+
+/// Describes the workload to spawn, loaded from `resources/scenario.ron` or the file given with
+/// `--scenario`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Scenario {
+    /// Number of balls created at startup.
+    pub ball_count: usize,
+    /// Balls added or removed at a time by the spawn and despawn keys.
+    pub batch_size: usize,
+    /// Where in the arena the balls start.
+    pub spawn_region: SpawnRegion,
+    /// Each velocity component is picked uniformly from `-velocity_range..velocity_range`.
+    pub velocity_range: f32,
+    /// Sprite used for every ball in `resources/spritesheet.ron`.
+    pub sprite_number: usize,
+    /// Seed for spawning, used unless `--seed` is given. A random seed is picked when neither is.
+    pub seed: Option<u64>,
+    /// How balls bounce off the walls, unless they have a material of their own.
+    pub wall_material: BounceMaterial,
+    /// Material given to every spawned ball, overriding `wall_material`.
+    pub ball_material: Option<BounceMaterial>,
+    /// Acceleration on every ball, in pixels per second squared.
+    pub gravity: Gravity,
+    /// Extra acceleration given to every spawned ball on top of `gravity`.
+    pub ball_acceleration: Option<Acceleration>,
+    /// Walls to bounce off. When not given, the arena is the window, or the dimensions in
+    /// `resources/headless_config.ron` without one.
+    pub arena: Option<ArenaBounds>,
+    /// What each wall of the arena does to the balls that reach it.
+    pub boundaries: Boundaries,
+    /// Makes the balls collide with each other, instead of passing through.
+    pub collisions: Option<CollisionConfig>,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            ball_count: 100_000,
+            batch_size: 1_000,
+            spawn_region: SpawnRegion::default(),
+            velocity_range: 50.0,
+            sprite_number: 0,
+            seed: None,
+            wall_material: BounceMaterial::default(),
+            ball_material: None,
+            gravity: Gravity::default(),
+            ball_acceleration: None,
+            arena: None,
+            boundaries: Boundaries::default(),
+            collisions: None,
+        }
+    }
+}
+
+/// A rectangle given as fractions of the arena's width and height, so `(0.0, 1.0)` spans the
+/// whole axis and `(0.5, 0.5)` is its centre.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SpawnRegion {
+    /// Fractions of the width the balls start between.
+    pub x: (f32, f32),
+    /// Fractions of the height the balls start between.
+    pub y: (f32, f32),
+}
+
+impl Default for SpawnRegion {
+    fn default() -> Self {
+        Self {
+            x: (0.5, 0.5),
+            y: (0.5, 0.5),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Saving the simulation and carrying on from where it was saved.
+
+use amethyst::{
+    config::Config,
+    core::transform::Transform,
+    ecs::{Join, ReadStorage},
+    prelude::{World, WorldExt},
+    renderer::sprite::{SpriteRender, SpriteSheetHandle},
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use crate::{
+    arena::ArenaBounds,
+    create_ball,
+    position::{self, Position2D},
+    random::SimulationRng,
+    Scenario, Velocity,
+};
+
+/// Everything needed to carry on a run from where it was saved: every ball, the arena and the
+/// state of the random number generator. Saved and loaded as RON with `Config`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    /// The walls.
+    pub arena: ArenaBounds,
+    /// The generator, as it was when saved.
+    pub rng: SimulationRng,
+    /// Every ball, in entity order.
+    pub balls: Vec<BallSnapshot>,
+}
+
+/// One ball in a `Snapshot`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BallSnapshot {
+    /// Where the ball was.
+    pub translation: [f32; 3],
+    /// How fast it was going.
+    pub velocity: [f32; 2],
+    /// Sprite the ball was drawn with, or `None` if it had none, as in a headless run.
+    pub sprite_number: Option<usize>,
+}
+
+impl Snapshot {
+    /// Copies the state of the world.
+    pub fn capture(world: &World) -> Self {
+        let (positions, transforms, velocities, sprites): (
+            ReadStorage<'_, Position2D>,
+            ReadStorage<'_, Transform>,
+            ReadStorage<'_, Velocity>,
+            ReadStorage<'_, SpriteRender>,
+        ) = world.system_data();
+
+        let balls = (
+            positions.maybe(),
+            transforms.maybe(),
+            &velocities,
+            sprites.maybe(),
+        )
+            .join()
+            .filter_map(|(position, transform, velocity, sprite)| {
+                Some(BallSnapshot {
+                    translation: position::translation(position, transform)?,
+                    velocity: [velocity.x, velocity.y],
+                    sprite_number: sprite.map(|sprite| sprite.sprite_number),
+                })
+            })
+            .collect();
+
+        Self {
+            arena: world.read_resource::<ArenaBounds>().clone(),
+            rng: world.read_resource::<SimulationRng>().clone(),
+            balls,
+        }
+    }
+
+    /// Creates the saved balls, in the order they were saved, instead of spawning new ones. They
+    /// get the components the `Scenario` gives every ball, and sprites only if a handle is given.
+    ///
+    /// The arena and generator are left alone; they have to be inserted before the game starts.
+    pub fn restore(&self, world: &mut World, sprite_sheet_handle: Option<&SpriteSheetHandle>) {
+        let scenario = world.read_resource::<Scenario>().clone();
+
+        for ball in &self.balls {
+            let [x, y, z] = ball.translation;
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(x, y, z);
+
+            let velocity = Velocity {
+                x: ball.velocity[0],
+                y: ball.velocity[1],
+            };
+
+            let sprite = sprite_sheet_handle.map(|sprite_sheet_handle| SpriteRender {
+                sprite_sheet: sprite_sheet_handle.clone(),
+                sprite_number: ball.sprite_number.unwrap_or(scenario.sprite_number),
+            });
+
+            create_ball(world, &scenario, transform, velocity, sprite);
+        }
+    }
+}
+
+/// Writes a snapshot of the world to `path`, logging the outcome.
+pub fn save(world: &World, path: &Path) {
+    let snapshot = Snapshot::capture(world);
+
+    match snapshot.write(path) {
+        Ok(()) => info!("Saved {} balls to {}", snapshot.balls.len(), path.display()),
+        Err(err) => error!("Failed to save snapshot: {}", err),
+    }
+}
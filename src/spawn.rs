@@ -0,0 +1,161 @@
+//! Creating and deleting balls.
+
+use amethyst::{
+    core::transform::Transform,
+    ecs::{Entity, Join},
+    prelude::{Builder, World, WorldExt},
+    renderer::sprite::{SpriteRender, SpriteSheetHandle},
+};
+use log::info;
+use rand::Rng;
+
+use crate::{
+    arena::ArenaBounds,
+    collision::{Mass, Radius},
+    position::{Position2D, PositionMode},
+    random::SimulationRng,
+    scenario::Scenario,
+    velocity::Velocity,
+};
+
+/// Creates `count` balls as described by the `Scenario` resource, with sprites only if a handle
+/// is given.
+pub fn spawn_balls(
+    world: &mut World,
+    count: usize,
+    sprite_sheet_handle: Option<&SpriteSheetHandle>,
+) {
+    let arena = world.read_resource::<ArenaBounds>().clone();
+    let scenario = world.read_resource::<Scenario>().clone();
+    let region = &scenario.spawn_region;
+    let range = scenario.velocity_range;
+
+    // Everything random is drawn before any entity is created, since creating entities needs
+    // the world while the generator is borrowed from it.
+    let balls = {
+        let mut rng = world.write_resource::<SimulationRng>();
+
+        (0..count)
+            .map(|_| {
+                let mut ball_transform = Transform::default();
+                let x = arena.min_x + arena.width() * sample(&mut *rng, region.x);
+                let y = arena.min_y + arena.height() * sample(&mut *rng, region.y);
+
+                ball_transform.set_translation_xyz(x, y, 0.);
+
+                let velocity = Velocity {
+                    x: sample(&mut *rng, (-range, range)),
+                    y: sample(&mut *rng, (-range, range)),
+                };
+
+                (ball_transform, velocity)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for (ball_transform, velocity) in balls {
+        let sprite = sprite_sheet_handle.map(|sprite_sheet_handle| SpriteRender {
+            sprite_sheet: sprite_sheet_handle.clone(),
+            sprite_number: scenario.sprite_number,
+        });
+
+        create_ball(world, &scenario, ball_transform, velocity, sprite);
+    }
+}
+
+/// Creates a ball with the components the `Scenario` gives every ball.
+pub fn create_ball(
+    world: &mut World,
+    scenario: &Scenario,
+    transform: Transform,
+    velocity: Velocity,
+    sprite: Option<SpriteRender>,
+) -> Entity {
+    let position_mode = *world.read_resource::<PositionMode>();
+    let mut ball = world.create_entity().with(velocity);
+
+    // Balls moved with a `Position2D` only need a `Transform` to be rendered.
+    match position_mode {
+        PositionMode::Transform => ball = ball.with(transform),
+        PositionMode::Position2D => {
+            let translation = transform.translation();
+            ball = ball.with(Position2D {
+                x: translation.x,
+                y: translation.y,
+            });
+
+            if sprite.is_some() {
+                ball = ball.with(transform);
+            }
+        }
+    }
+
+    if let Some(material) = &scenario.ball_material {
+        ball = ball.with(material.clone());
+    }
+
+    if let Some(acceleration) = &scenario.ball_acceleration {
+        ball = ball.with(acceleration.clone());
+    }
+
+    if let Some(collisions) = &scenario.collisions {
+        ball = ball
+            .with(Radius(collisions.radius))
+            .with(Mass(collisions.mass));
+    }
+
+    if let Some(sprite) = sprite {
+        ball = ball.with(sprite);
+    }
+
+    ball.build()
+}
+
+/// Spawns or despawns a batch of balls for an input action. Other actions are ignored.
+pub fn apply_action(
+    world: &mut World,
+    action: &str,
+    sprite_sheet_handle: Option<&SpriteSheetHandle>,
+) {
+    let batch_size = world.read_resource::<Scenario>().batch_size;
+
+    match action {
+        "spawn_balls" => spawn_balls(world, batch_size, sprite_sheet_handle),
+        "despawn_balls" => despawn_balls(world, batch_size),
+        _ => return,
+    }
+
+    info!("{} balls", ball_count(world));
+}
+
+/// Deletes up to `count` balls.
+pub fn despawn_balls(world: &mut World, count: usize) {
+    let balls = {
+        let entities = world.entities();
+        let velocities = world.read_storage::<Velocity>();
+
+        (&entities, &velocities)
+            .join()
+            .take(count)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>()
+    };
+
+    world
+        .delete_entities(&balls)
+        .expect("the balls were just joined, so they are alive");
+}
+
+/// Picks a value in `low..high`, or `low` itself when the range is empty.
+fn sample<R: Rng>(rng: &mut R, (low, high): (f32, f32)) -> f32 {
+    if low < high {
+        rng.gen_range(low, high)
+    } else {
+        low
+    }
+}
+
+/// Number of balls alive.
+pub fn ball_count(world: &World) -> usize {
+    (&world.read_storage::<Velocity>()).join().count()
+}
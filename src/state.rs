@@ -0,0 +1,186 @@
+//! The state of a windowed game.
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::transform::Transform,
+    input::InputEvent,
+    prelude::{Builder, World, WorldExt},
+    renderer::{
+        camera::{Camera, Projection},
+        formats::texture::ImageFormat,
+        sprite::{SpriteSheet, SpriteSheetFormat, SpriteSheetHandle},
+        Texture,
+    },
+    window::ScreenDimensions,
+    GameData, SimpleState, SimpleTrans, StateData, StateEvent, Trans,
+};
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    benchmark,
+    ramp::Ramp,
+    replay::{Replay, ReplayStep},
+    scenario::Scenario,
+    snapshot::{self, Snapshot},
+    spawn::{apply_action, spawn_balls},
+    stats,
+};
+
+/// Renders the balls, adding more while ramping if there is a ramp, or starting from a snapshot
+/// or replaying a recording if given one.
+pub struct State {
+    ramp: Option<Ramp>,
+    /// Balls to start with instead of spawning the scenario's.
+    snapshot: Option<Snapshot>,
+    /// Where snapshots are saved, both when asked for and when the game stops.
+    save_snapshot: Option<PathBuf>,
+    replay: Option<Replay>,
+    sprite_sheet_handle: Option<SpriteSheetHandle>,
+}
+
+impl State {
+    /// A game ramping up with `ramp` if given one, starting from `snapshot` instead of spawning
+    /// if given one, saving snapshots to `save_snapshot` and recording or playing back `replay`.
+    pub fn new(
+        ramp: Option<Ramp>,
+        snapshot: Option<Snapshot>,
+        save_snapshot: Option<PathBuf>,
+        replay: Option<Replay>,
+    ) -> Self {
+        Self {
+            ramp,
+            snapshot,
+            save_snapshot,
+            replay,
+            sprite_sheet_handle: None,
+        }
+    }
+}
+
+impl SimpleState for State {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        let mut camera_transform = Transform::default();
+        camera_transform.set_translation_z(1.0);
+
+        let (width, height) = get_dimensions(world);
+
+        world
+            .create_entity()
+            .with(Camera::from(Projection::orthographic(
+                0., width, 0., -height, 0.1, 2000.0,
+            )))
+            .with(camera_transform)
+            .build();
+
+        let sprite_sheet_handle = load_sprite_sheet(world);
+
+        match self.snapshot.take() {
+            Some(snapshot) => snapshot.restore(world, Some(&sprite_sheet_handle)),
+            None => {
+                let ball_count = world.read_resource::<Scenario>().ball_count;
+                spawn_balls(world, ball_count, Some(&sprite_sheet_handle));
+            }
+        }
+
+        self.sprite_sheet_handle = Some(sprite_sheet_handle);
+
+        stats::create_overlay(world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Input(InputEvent::ActionPressed(action)) = event {
+            match action.as_str() {
+                "spawn_balls" | "despawn_balls" => {
+                    let live = match &mut self.replay {
+                        Some(replay) => replay.input(&action),
+                        None => true,
+                    };
+
+                    if live {
+                        apply_action(data.world, &action, self.sprite_sheet_handle.as_ref());
+                    }
+                }
+                "toggle_stats" => stats::toggle_overlay(data.world),
+                "save_snapshot" => {
+                    let path = self
+                        .save_snapshot
+                        .as_deref()
+                        .unwrap_or_else(|| Path::new("snapshot.ron"));
+                    snapshot::save(data.world, path);
+                }
+                _ => {}
+            }
+        }
+
+        Trans::None
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if let Some(replay) = &mut self.replay {
+            let sprite_sheet_handle = self.sprite_sheet_handle.as_ref();
+            let step = replay.update(data.world, |world, action| {
+                apply_action(world, action, sprite_sheet_handle)
+            });
+
+            if step == ReplayStep::Finished {
+                return Trans::Quit;
+            }
+        }
+
+        match &mut self.ramp {
+            Some(ramp) => ramp.advance(data.world, self.sprite_sheet_handle.as_ref()),
+            None => Trans::None,
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        benchmark::finish(data.world);
+
+        if let Some(replay) = &mut self.replay {
+            replay.finish(data.world);
+        }
+
+        if let Some(path) = &self.save_snapshot {
+            snapshot::save(data.world, path);
+        }
+    }
+}
+
+/// The size of the window.
+fn get_dimensions(world: &mut World) -> (f32, f32) {
+    let screen_dimensions = world.read_resource::<ScreenDimensions>();
+
+    (screen_dimensions.width(), screen_dimensions.height())
+}
+
+/// Starts loading the ball sprites.
+fn load_sprite_sheet(world: &mut World) -> SpriteSheetHandle {
+    let texture_handle = {
+        let loader = world.read_resource::<Loader>();
+        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+
+        loader.load(
+            "assets/spritesheet.png",
+            ImageFormat::default(),
+            (),
+            &texture_storage,
+        )
+    };
+
+    let loader = world.read_resource::<Loader>();
+    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
+
+    loader.load(
+        "resources/spritesheet.ron",
+        SpriteSheetFormat(texture_handle),
+        (),
+        &sprite_sheet_store,
+    )
+}
@@ -0,0 +1,306 @@
+//! Frame statistics and the overlay that shows them.
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::{bundle::SystemBundle, timing::Time, Hidden},
+    ecs::{
+        prelude::DispatcherBuilder, Entity, Join, Read, ReadExpect, ReadStorage, System,
+        WriteExpect, WriteStorage,
+    },
+    error::Error,
+    prelude::{Builder, World, WorldExt},
+    ui::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform},
+};
+
+use std::collections::VecDeque;
+
+use crate::{
+    timing::{FrameClock, SystemTimings},
+    Velocity,
+};
+
+/// Frames averaged for the frame time shown in the overlay.
+const WINDOW_FRAMES: usize = 60;
+
+/// Keeps the `Stats` up to date every frame, and copies them into the overlay once it exists.
+pub struct StatsBundle;
+
+impl<'a, 'b> SystemBundle<'a, 'b> for StatsBundle {
+    fn build(
+        self,
+        world: &mut World,
+        builder: &mut DispatcherBuilder<'a, 'b>,
+    ) -> Result<(), Error> {
+        world.insert(Stats::new(WINDOW_FRAMES));
+        // Thread-local, as `SystemTimings` asks.
+        builder.add_thread_local(StatsSystem);
+        builder.add_thread_local(StatsOverlaySystem);
+
+        Ok(())
+    }
+}
+
+/// Rolling frame time, ball count and the time each timed system took, formatted for display by
+/// `text`.
+pub struct Stats {
+    window_frames: usize,
+    frame_times: RollingMean,
+    /// Rolling time of every system recorded with `record_system`, in the order first recorded.
+    system_times: Vec<(&'static str, RollingMean)>,
+    entity_count: usize,
+}
+
+impl Stats {
+    /// Averages the frame and system times over the last `window_frames` frames.
+    pub fn new(window_frames: usize) -> Self {
+        Self {
+            window_frames,
+            frame_times: RollingMean::new(window_frames),
+            system_times: Vec::new(),
+            entity_count: 0,
+        }
+    }
+
+    /// Feeds in how long the last frame took and how many balls it moved.
+    pub fn record_frame(&mut self, delta_seconds: f32, entity_count: usize) {
+        self.frame_times.push(delta_seconds);
+        self.entity_count = entity_count;
+    }
+
+    /// Feeds in how long the system called `name` took in the last frame.
+    pub fn record_system(&mut self, name: &'static str, seconds: f32) {
+        let index = match self
+            .system_times
+            .iter()
+            .position(|(recorded, _)| *recorded == name)
+        {
+            Some(index) => index,
+            None => {
+                self.system_times
+                    .push((name, RollingMean::new(self.window_frames)));
+                self.system_times.len() - 1
+            }
+        };
+
+        self.system_times[index].1.push(seconds);
+    }
+
+    /// Mean frame time over the window, or `None` before the first frame.
+    pub fn mean_frame_seconds(&self) -> Option<f32> {
+        self.frame_times.mean()
+    }
+
+    /// The stats as lines of text: FPS, frame time and ball count, then the time of each system.
+    pub fn text(&self) -> String {
+        let (fps, frame_time) = match self.mean_frame_seconds() {
+            Some(seconds) if seconds > 0.0 => (
+                format!("{:.1}", 1.0 / seconds),
+                format!("{:.2} ms", seconds * 1000.0),
+            ),
+            _ => ("-".to_string(), "-".to_string()),
+        };
+
+        let mut text = format!(
+            "FPS: {}\nFrame: {}\nBalls: {}",
+            fps, frame_time, self.entity_count
+        );
+
+        for (name, times) in &self.system_times {
+            if let Some(seconds) = times.mean() {
+                text.push_str(&format!("\n{}: {:.2} ms", name, seconds * 1000.0));
+            }
+        }
+
+        text
+    }
+}
+
+/// Mean of the last few values pushed.
+struct RollingMean {
+    window: usize,
+    values: VecDeque<f32>,
+    total: f32,
+}
+
+impl RollingMean {
+    fn new(window: usize) -> Self {
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+            total: 0.0,
+        }
+    }
+
+    fn push(&mut self, value: f32) {
+        if self.values.len() == self.window {
+            if let Some(oldest) = self.values.pop_front() {
+                self.total -= oldest;
+            }
+        }
+
+        self.values.push_back(value);
+        self.total += value;
+    }
+
+    fn mean(&self) -> Option<f32> {
+        if self.values.is_empty() {
+            None
+        } else {
+            Some(self.total / self.values.len() as f32)
+        }
+    }
+}
+
+/// The text entity showing the `Stats` in the corner of the window.
+pub struct StatsOverlay {
+    entity: Entity,
+}
+
+/// Creates the overlay in the top left corner of the window.
+pub fn create_overlay(world: &mut World) {
+    let font = {
+        let loader = world.read_resource::<Loader>();
+        let font_storage = world.read_resource::<AssetStorage<FontAsset>>();
+
+        get_default_font(&loader, &font_storage)
+    };
+
+    let transform = UiTransform::new(
+        "stats".to_string(),
+        Anchor::TopLeft,
+        Anchor::TopLeft,
+        10.0,
+        -10.0,
+        1.0,
+        400.0,
+        400.0,
+    );
+
+    let mut text = UiText::new(font, String::new(), [1.0, 1.0, 1.0, 1.0], 16.0);
+    text.line_mode = LineMode::Wrap;
+    text.align = Anchor::TopLeft;
+
+    let entity = world.create_entity().with(transform).with(text).build();
+
+    world.insert(StatsOverlay { entity });
+}
+
+/// Shows the overlay if it is hidden, and hides it otherwise.
+pub fn toggle_overlay(world: &mut World) {
+    let entity = match world.try_fetch::<StatsOverlay>() {
+        Some(overlay) => overlay.entity,
+        None => return,
+    };
+
+    let mut hidden = world.write_storage::<Hidden>();
+
+    if hidden.remove(entity).is_none() {
+        hidden
+            .insert(entity, Hidden)
+            .expect("the overlay is never deleted");
+    }
+}
+
+struct StatsSystem;
+
+impl<'s> System<'s> for StatsSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        Read<'s, FrameClock>,
+        ReadStorage<'s, Velocity>,
+        Read<'s, SystemTimings>,
+        WriteExpect<'s, Stats>,
+    );
+
+    fn run(&mut self, (time, clock, velocities, timings, mut stats): Self::SystemData) {
+        // `Time` holds the recorded frame time while a recording is played back.
+        if let Some(delta_seconds) = clock.delta_seconds(time.frame_number()) {
+            stats.record_frame(delta_seconds, (&velocities).join().count());
+        }
+
+        for (name, seconds) in timings.latest_seconds() {
+            stats.record_system(name, seconds);
+        }
+    }
+}
+
+/// Writes the `Stats` into the overlay's text while it is shown.
+struct StatsOverlaySystem;
+
+impl<'s> System<'s> for StatsOverlaySystem {
+    type SystemData = (
+        ReadExpect<'s, Stats>,
+        Option<Read<'s, StatsOverlay>>,
+        ReadStorage<'s, Hidden>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (stats, overlay, hidden, mut texts): Self::SystemData) {
+        let entity = match overlay {
+            Some(overlay) => overlay.entity,
+            None => return,
+        };
+
+        if hidden.contains(entity) {
+            return;
+        }
+
+        if let Some(text) = texts.get_mut(entity) {
+            text.text = stats.text();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_mean_is_empty_until_pushed() {
+        let mut mean = RollingMean::new(3);
+        assert_eq!(mean.mean(), None);
+
+        mean.push(2.0);
+        assert_eq!(mean.mean(), Some(2.0));
+    }
+
+    #[test]
+    fn rolling_mean_forgets_values_past_the_window() {
+        let mut mean = RollingMean::new(2);
+
+        for value in &[1.0, 3.0, 5.0, 7.0] {
+            mean.push(*value);
+        }
+
+        assert_eq!(mean.mean(), Some(6.0));
+    }
+
+    #[test]
+    fn text_shows_dashes_before_the_first_frame() {
+        assert_eq!(Stats::new(60).text(), "FPS: -\nFrame: -\nBalls: 0");
+    }
+
+    #[test]
+    fn text_shows_the_mean_frame_and_ball_count() {
+        let mut stats = Stats::new(2);
+        stats.record_frame(0.5, 10);
+        stats.record_frame(0.01, 20);
+        stats.record_frame(0.03, 30);
+
+        assert_eq!(stats.text(), "FPS: 50.0\nFrame: 20.00 ms\nBalls: 30");
+    }
+
+    #[test]
+    fn text_lists_each_system_in_the_order_first_recorded() {
+        let mut stats = Stats::new(60);
+        stats.record_frame(0.02, 1);
+        stats.record_system("movement_system", 0.001);
+        stats.record_system("bounce_system", 0.002);
+        stats.record_system("movement_system", 0.003);
+
+        assert_eq!(
+            stats.text(),
+            "FPS: 50.0\nFrame: 20.00 ms\nBalls: 1\nmovement_system: 2.00 ms\nbounce_system: 2.00 ms"
+        );
+    }
+}
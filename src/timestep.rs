@@ -0,0 +1,60 @@
+//! Advancing the simulation in fixed steps.
+
+use amethyst::{
+    core::timing::Time,
+    ecs::{Read, System, WriteExpect},
+};
+
+/// Splits the time since the last frame into whole steps of a fixed size, so the simulation
+/// advances the same way whatever the frame rate is. Time left over carries into the next frame.
+pub struct FixedTimestep {
+    step_seconds: f32,
+    max_substeps: u32,
+    accumulator: f32,
+    substeps: u32,
+}
+
+impl FixedTimestep {
+    /// Steps of `step_seconds`, at most `max_substeps` of them a frame.
+    pub fn new(step_seconds: f32, max_substeps: u32) -> Self {
+        Self {
+            step_seconds,
+            max_substeps,
+            accumulator: 0.0,
+            substeps: 0,
+        }
+    }
+
+    /// Length of every step, in seconds.
+    pub fn step_seconds(&self) -> f32 {
+        self.step_seconds
+    }
+
+    /// Steps to run this frame.
+    pub fn substeps(&self) -> u32 {
+        self.substeps
+    }
+
+    /// Adds the last frame's time and works out how many steps are due. When more than
+    /// `max_substeps` are due the extra ones are dropped, slowing the simulation down rather than
+    /// letting a slow frame make the next one slower still.
+    pub fn advance(&mut self, delta_seconds: f32) {
+        self.accumulator += delta_seconds;
+
+        let due = (self.accumulator / self.step_seconds) as u32;
+
+        self.accumulator -= due as f32 * self.step_seconds;
+        self.substeps = due.min(self.max_substeps);
+    }
+}
+
+/// Advances the `FixedTimestep` resource once per frame, before anything reads it.
+pub struct FixedTimestepSystem;
+
+impl<'s> System<'s> for FixedTimestepSystem {
+    type SystemData = (Read<'s, Time>, WriteExpect<'s, FixedTimestep>);
+
+    fn run(&mut self, (time, mut timestep): Self::SystemData) {
+        timestep.advance(time.delta_seconds());
+    }
+}
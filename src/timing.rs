@@ -0,0 +1,171 @@
+//! Timing how long each frame and each system takes.
+
+use amethyst::ecs::{Read, RunningTime, System, SystemData, World};
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// How long each timed system took the last time it ran, in the order they first ran.
+///
+/// Systems record into it through a shared `Read`, so timing them doesn't stop them running in
+/// parallel. Read it from a thread-local system, which runs once every other system of the frame
+/// has finished, to see all of one frame's timings.
+#[derive(Default)]
+pub struct SystemTimings {
+    latest: Mutex<Vec<(&'static str, Duration)>>,
+    dispatch_start: Mutex<Option<Instant>>,
+}
+
+impl SystemTimings {
+    /// Replaces the time recorded for the system called `name`.
+    pub fn record(&self, name: &'static str, duration: Duration) {
+        let mut latest = self
+            .latest
+            .lock()
+            .expect("no system panics while recording");
+
+        match latest.iter_mut().find(|(recorded, _)| *recorded == name) {
+            Some((_, recorded)) => *recorded = duration,
+            None => latest.push((name, duration)),
+        }
+    }
+
+    /// Marks the start of the dispatch, for `finish_dispatch` to time.
+    pub fn start_dispatch(&self) {
+        *self
+            .dispatch_start
+            .lock()
+            .expect("no system panics while recording") = Some(Instant::now());
+    }
+
+    /// Records the time since `start_dispatch` under `"dispatch"`.
+    pub fn finish_dispatch(&self) {
+        let start = self
+            .dispatch_start
+            .lock()
+            .expect("no system panics while recording")
+            .take();
+
+        if let Some(start) = start {
+            self.record("dispatch", start.elapsed());
+        }
+    }
+
+    /// The latest time of every system that has run, in seconds.
+    pub fn latest_seconds(&self) -> Vec<(&'static str, f32)> {
+        self.latest
+            .lock()
+            .expect("no system panics while recording")
+            .iter()
+            .map(|(name, duration)| (*name, duration.as_secs_f32()))
+            .collect()
+    }
+}
+
+/// The real time between frames, measured with the system clock.
+///
+/// `Time` can't be trusted for it while a recording is played back, since the recorded frame time
+/// replaces the real one there. The first reader in a frame measures it and later readers in the
+/// same frame get the same value.
+#[derive(Default)]
+pub struct FrameClock {
+    last_tick: Mutex<Option<FrameTick>>,
+}
+
+struct FrameTick {
+    frame_number: u64,
+    at: Instant,
+    delta_seconds: Option<f32>,
+}
+
+impl FrameClock {
+    /// Seconds since the frame before `frame_number`, or `None` on the first frame measured.
+    pub fn delta_seconds(&self, frame_number: u64) -> Option<f32> {
+        let mut last_tick = self
+            .last_tick
+            .lock()
+            .expect("no system panics while measuring");
+
+        match &*last_tick {
+            Some(tick) if tick.frame_number == frame_number => tick.delta_seconds,
+            previous => {
+                let now = Instant::now();
+                let delta_seconds = previous
+                    .as_ref()
+                    .map(|tick| now.duration_since(tick.at).as_secs_f32());
+
+                *last_tick = Some(FrameTick {
+                    frame_number,
+                    at: now,
+                    delta_seconds,
+                });
+
+                delta_seconds
+            }
+        }
+    }
+}
+
+/// Runs a system and records how long it took in `SystemTimings` under its dispatcher name.
+pub struct Timed<S> {
+    name: &'static str,
+    system: S,
+}
+
+impl<S> Timed<S> {
+    /// Times `system`, recording it as `name`, which should match the name it is dispatched as.
+    pub fn new(name: &'static str, system: S) -> Self {
+        Self { name, system }
+    }
+}
+
+impl<'s, S> System<'s> for Timed<S>
+where
+    S: System<'s>,
+    S::SystemData: SystemData<'s>,
+{
+    type SystemData = (S::SystemData, Read<'s, SystemTimings>);
+
+    fn run(&mut self, (data, timings): Self::SystemData) {
+        let start = Instant::now();
+        self.system.run(data);
+        timings.record(self.name, start.elapsed());
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.system.setup(world);
+        world
+            .entry::<SystemTimings>()
+            .or_insert_with(SystemTimings::default);
+    }
+}
+
+/// Starts timing the dispatch. Add it before any other system, followed by a barrier, so that
+/// everything else runs after it.
+pub struct DispatchStartSystem;
+
+impl<'s> System<'s> for DispatchStartSystem {
+    type SystemData = Read<'s, SystemTimings>;
+
+    fn run(&mut self, timings: Self::SystemData) {
+        timings.start_dispatch();
+    }
+}
+
+/// Records how long the dispatch took since `DispatchStartSystem` ran. Add it as a thread-local
+/// system after every other one but those that read the timings.
+pub struct DispatchEndSystem;
+
+impl<'s> System<'s> for DispatchEndSystem {
+    type SystemData = Read<'s, SystemTimings>;
+
+    fn run(&mut self, timings: Self::SystemData) {
+        timings.finish_dispatch();
+    }
+}
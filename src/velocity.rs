@@ -0,0 +1,131 @@
+//! How fast the balls move, and the storage that is kept in.
+
+use amethyst::ecs::{Component, Join, WriteStorage};
+
+use crate::position::Position;
+
+/// How fast a ball moves, in pixels per second. Every ball has one.
+#[derive(Clone, Debug)]
+pub struct Velocity {
+    /// Horizontal speed.
+    pub x: f32,
+    /// Vertical speed.
+    pub y: f32,
+}
+
+impl Component for Velocity {
+    type Storage = VelocityStorage;
+}
+
+// The storage `Velocity` lives in is picked with a cargo feature, so the storages can be
+// benchmarked against each other. `DenseVecStorage` is used when none of them is enabled.
+#[cfg(any(
+    all(feature = "velocity-vec-storage", feature = "velocity-hashmap-storage"),
+    all(feature = "velocity-vec-storage", feature = "velocity-flagged-storage"),
+    all(
+        feature = "velocity-hashmap-storage",
+        feature = "velocity-flagged-storage"
+    ),
+))]
+compile_error!("only one of the `velocity-*-storage` features can be enabled at a time");
+
+#[cfg(feature = "velocity-vec-storage")]
+type VelocityStorage = amethyst::ecs::VecStorage<Velocity>;
+/// Name of the storage `Velocity` lives in, for benchmark reports.
+#[cfg(feature = "velocity-vec-storage")]
+pub const VELOCITY_STORAGE: &str = "VecStorage";
+
+#[cfg(feature = "velocity-hashmap-storage")]
+type VelocityStorage = amethyst::ecs::HashMapStorage<Velocity>;
+/// Name of the storage `Velocity` lives in, for benchmark reports.
+#[cfg(feature = "velocity-hashmap-storage")]
+pub const VELOCITY_STORAGE: &str = "HashMapStorage";
+
+#[cfg(feature = "velocity-flagged-storage")]
+type VelocityStorage =
+    amethyst::ecs::FlaggedStorage<Velocity, amethyst::ecs::DenseVecStorage<Velocity>>;
+/// Name of the storage `Velocity` lives in, for benchmark reports.
+#[cfg(feature = "velocity-flagged-storage")]
+pub const VELOCITY_STORAGE: &str = "FlaggedStorage<DenseVecStorage>";
+
+#[cfg(not(any(
+    feature = "velocity-vec-storage",
+    feature = "velocity-hashmap-storage",
+    feature = "velocity-flagged-storage"
+)))]
+type VelocityStorage = amethyst::ecs::DenseVecStorage<Velocity>;
+/// Name of the storage `Velocity` lives in, for benchmark reports.
+#[cfg(not(any(
+    feature = "velocity-vec-storage",
+    feature = "velocity-hashmap-storage",
+    feature = "velocity-flagged-storage"
+)))]
+pub const VELOCITY_STORAGE: &str = "DenseVecStorage";
+
+// A `FlaggedStorage` can't be `par_join`ed mutably, so with `velocity-flagged-storage` the joins
+// that write `Velocity` are collected before being handed to the thread pool.
+
+/// Calls `f` on every item of a join that writes `Velocity`, spread over the thread pool.
+#[cfg(not(feature = "velocity-flagged-storage"))]
+pub fn par_for_each<J, F>(join: J, f: F)
+where
+    J: Join + amethyst::ecs::ParJoin + Send,
+    J::Mask: Send + Sync,
+    J::Type: Send,
+    J::Value: Send,
+    F: Fn(J::Type) + Send + Sync,
+{
+    use amethyst::ecs::rayon::prelude::ParallelIterator;
+
+    join.par_join().for_each(f);
+}
+
+/// Calls `f` on every item of a join that writes `Velocity`, spread over the thread pool.
+#[cfg(feature = "velocity-flagged-storage")]
+pub fn par_for_each<J, F>(join: J, f: F)
+where
+    J: Join,
+    J::Type: Send,
+    F: Fn(J::Type) + Send + Sync,
+{
+    crate::position::collect_filter_map(join, |item| {
+        f(item);
+        None::<()>
+    });
+}
+
+/// `Position::par_filter_map`, for joins in `rest` that write `Velocity`.
+#[cfg(not(feature = "velocity-flagged-storage"))]
+pub fn par_filter_map<'a, P, R, F, T>(
+    positions: &'a mut WriteStorage<'_, P>,
+    rest: R,
+    f: F,
+) -> Vec<T>
+where
+    P: Position,
+    R: Join + amethyst::ecs::ParJoin + Send,
+    R::Mask: Send + Sync,
+    R::Type: Send,
+    R::Value: Send,
+    F: Fn((&'a mut P, R::Type)) -> Option<T> + Send + Sync,
+    T: Send,
+{
+    P::par_filter_map(positions, rest, f)
+}
+
+/// `Position::par_filter_map`, for joins in `rest` that write `Velocity`.
+#[cfg(feature = "velocity-flagged-storage")]
+pub fn par_filter_map<'a, P, R, F, T>(
+    positions: &'a mut WriteStorage<'_, P>,
+    rest: R,
+    f: F,
+) -> Vec<T>
+where
+    P: Position,
+    R: Join,
+    R::Type: Send,
+    F: Fn((&'a mut P, R::Type)) -> Option<T> + Send + Sync,
+    T: Send,
+{
+    crate::position::collect_filter_map((positions, rest), f)
+}
@@ -0,0 +1,64 @@
+//! Keeping the camera and the arena the size of the window.
+
+use amethyst::{
+    ecs::{Join, ReadExpect, System, SystemData, World, WriteExpect, WriteStorage},
+    renderer::camera::Camera,
+    window::ScreenDimensions,
+};
+
+use crate::arena::ArenaBounds;
+
+/// Keeps the camera, and the arena unless it was given its own bounds, the size of the window.
+/// Creates the arena from the window when it is set up, if there isn't one yet.
+pub struct WindowResizeSystem {
+    last_dimensions: ScreenDimensions,
+    follow_window: bool,
+}
+
+impl WindowResizeSystem {
+    /// Resizes the arena along with the camera when `follow_window` is set.
+    pub fn new(follow_window: bool) -> Self {
+        Self {
+            last_dimensions: ScreenDimensions::new(0, 0, 0.0),
+            follow_window,
+        }
+    }
+}
+
+impl<'s> System<'s> for WindowResizeSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        WriteStorage<'s, Camera>,
+        WriteExpect<'s, ArenaBounds>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        <Self::SystemData as SystemData>::setup(world);
+
+        if !world.has_value::<ArenaBounds>() {
+            // The window normally exists by now. If it doesn't, the first run resizes the arena.
+            let arena = world.try_fetch::<ScreenDimensions>().map_or_else(
+                || ArenaBounds::new(0.0, 0.0),
+                |screen| ArenaBounds::from_screen(&screen),
+            );
+            world.insert(arena);
+        }
+    }
+
+    fn run(&mut self, (screen_dimensions, mut cameras, mut arena): Self::SystemData) {
+        if self.last_dimensions != *screen_dimensions {
+            for camera in (&mut cameras).join() {
+                if let Some(ortho) = camera.projection_mut().as_orthographic_mut() {
+                    ortho.set_bottom_and_top(0., -screen_dimensions.height());
+                    ortho.set_left_and_right(0., screen_dimensions.width());
+                }
+            }
+
+            if self.follow_window {
+                *arena = ArenaBounds::from_screen(&screen_dimensions);
+            }
+
+            self.last_dimensions = screen_dimensions.clone();
+        }
+    }
+}
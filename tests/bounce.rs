@@ -0,0 +1,150 @@
+mod common;
+
+use bounce::{
+    arena::ArenaBounds,
+    boundary::{Boundaries, WallPolicy},
+    position::PositionMode,
+    timestep::FixedTimestep,
+    BounceBundle,
+};
+
+use crate::common::{assert_close, Harness};
+
+#[test]
+fn ball_moves_by_its_velocity() {
+    let mut harness = Harness::new(100.0, 100.0);
+    let ball = harness.spawn([20.0, 30.0], [10.0, -5.0]);
+
+    harness.step(4, 0.25);
+
+    let [x, y] = harness.position(ball).unwrap();
+    assert_close(x, 30.0);
+    assert_close(y, 25.0);
+    assert_eq!(harness.velocity(ball), Some([10.0, -5.0]));
+}
+
+#[test]
+fn ball_moving_right_flips_at_the_wall() {
+    let mut harness = Harness::new(100.0, 100.0);
+    let ball = harness.spawn([90.0, 50.0], [50.0, 0.0]);
+
+    harness.step(1, 0.1);
+    assert_close(harness.velocity(ball).unwrap()[0], 50.0);
+
+    harness.step(1, 0.1);
+    assert_close(harness.position(ball).unwrap()[0], 100.0);
+    assert_close(harness.velocity(ball).unwrap()[0], -50.0);
+
+    harness.step(1, 0.1);
+    assert_close(harness.position(ball).unwrap()[0], 95.0);
+}
+
+#[test]
+fn every_wall_reflects() {
+    let mut harness = Harness::new(100.0, 100.0);
+    let balls = [
+        (
+            harness.spawn([50.0, 50.0], [-200.0, 0.0]),
+            [0.0, 50.0],
+            [200.0, 0.0],
+        ),
+        (
+            harness.spawn([50.0, 50.0], [200.0, 0.0]),
+            [100.0, 50.0],
+            [-200.0, 0.0],
+        ),
+        (
+            harness.spawn([50.0, 50.0], [0.0, -200.0]),
+            [50.0, 0.0],
+            [0.0, 200.0],
+        ),
+        (
+            harness.spawn([50.0, 50.0], [0.0, 200.0]),
+            [50.0, 100.0],
+            [0.0, -200.0],
+        ),
+    ];
+
+    harness.step(3, 0.1);
+
+    for (ball, position, velocity) in &balls {
+        let [x, y] = harness.position(*ball).unwrap();
+        let [velocity_x, velocity_y] = harness.velocity(*ball).unwrap();
+
+        assert_close(x, position[0]);
+        assert_close(y, position[1]);
+        assert_close(velocity_x, velocity[0]);
+        assert_close(velocity_y, velocity[1]);
+    }
+}
+
+#[test]
+fn absorbing_wall_deletes_the_ball() {
+    let mut harness = Harness::new(100.0, 100.0);
+    harness.world.insert(Boundaries {
+        right: WallPolicy::Absorb,
+        ..Boundaries::default()
+    });
+    let ball = harness.spawn([90.0, 50.0], [50.0, 0.0]);
+
+    harness.step(1, 0.1);
+    assert!(harness.position(ball).is_some());
+
+    harness.step(1, 0.1);
+    assert_eq!(harness.position(ball), None);
+}
+
+#[test]
+fn wrapping_wall_moves_the_ball_across() {
+    let mut harness = Harness::new(100.0, 100.0);
+    harness.world.insert(Boundaries {
+        right: WallPolicy::Wrap,
+        ..Boundaries::default()
+    });
+    let ball = harness.spawn([95.0, 50.0], [100.0, 0.0]);
+
+    harness.step(1, 0.1);
+
+    assert_close(harness.position(ball).unwrap()[0], 5.0);
+    assert_close(harness.velocity(ball).unwrap()[0], 100.0);
+}
+
+#[test]
+fn open_wall_lets_the_ball_leave() {
+    let mut harness = Harness::new(100.0, 100.0);
+    harness.world.insert(Boundaries {
+        right: WallPolicy::Open,
+        ..Boundaries::default()
+    });
+    let ball = harness.spawn([95.0, 50.0], [100.0, 0.0]);
+
+    harness.step(2, 0.1);
+
+    assert_close(harness.position(ball).unwrap()[0], 115.0);
+    assert_close(harness.velocity(ball).unwrap()[0], 100.0);
+}
+
+#[test]
+fn every_way_of_running_the_systems_bounces_the_same() {
+    let bundles = vec![
+        BounceBundle::new(),
+        BounceBundle::new().with_parallel(true),
+        BounceBundle::new().with_fused(true),
+        BounceBundle::new().with_fused(true).with_parallel(true),
+        BounceBundle::new().with_fixed_timestep(Some(FixedTimestep::new(0.05, 8))),
+        BounceBundle::new().with_position_mode(PositionMode::Position2D),
+        BounceBundle::new()
+            .with_fused(true)
+            .with_position_mode(PositionMode::Position2D),
+    ];
+
+    for bundle in bundles {
+        let mut harness = Harness::with_bundle(ArenaBounds::new(100.0, 100.0), bundle);
+        let ball = harness.spawn([90.0, 50.0], [50.0, 0.0]);
+
+        harness.step(3, 0.1);
+
+        assert_close(harness.position(ball).unwrap()[0], 95.0);
+        assert_close(harness.velocity(ball).unwrap()[0], -50.0);
+    }
+}
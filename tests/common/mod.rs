@@ -0,0 +1,123 @@
+//! Runs the `BounceBundle` systems without a window, for tests to step and inspect.
+
+// Each test binary uses only some of the helpers.
+#![allow(dead_code)]
+
+use amethyst::{
+    core::{bundle::SystemBundle, timing::Time, transform::Transform},
+    ecs::{Dispatcher, DispatcherBuilder, Entity, ReadStorage},
+    prelude::{World, WorldExt},
+};
+
+use bounce::{
+    arena::ArenaBounds,
+    create_ball,
+    position::{self, Position2D},
+    BounceBundle, Scenario, Velocity,
+};
+
+/// A `World` with the `BounceBundle` systems in a fixed arena, advanced frame by frame with
+/// whatever frame time the test picks.
+pub struct Harness {
+    pub world: World,
+    dispatcher: Dispatcher<'static, 'static>,
+}
+
+impl Harness {
+    /// An arena `width` by `height` with its corner at the origin, simulated with the default
+    /// bundle.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self::with_arena(ArenaBounds::new(width, height))
+    }
+
+    /// The given arena, which need not have its corner at the origin, simulated with the default
+    /// bundle.
+    pub fn with_arena(arena: ArenaBounds) -> Self {
+        Self::with_bundle(arena, BounceBundle::new())
+    }
+
+    /// `arena` simulated with `bundle`, which is made headless and given the arena.
+    pub fn with_bundle(arena: ArenaBounds, bundle: BounceBundle) -> Self {
+        let mut world = World::new();
+        world.insert(Time::default());
+        world.register::<Transform>();
+        world.register::<Position2D>();
+
+        let mut builder = DispatcherBuilder::new();
+        bundle
+            .headless()
+            .with_arena(Some(arena))
+            .build(&mut world, &mut builder)
+            .expect("a headless bundle with an arena builds");
+
+        let mut dispatcher = builder.build();
+        dispatcher.setup(&mut world);
+
+        Self { world, dispatcher }
+    }
+
+    /// Creates a ball at `position` moving at `velocity`, with the default scenario's
+    /// components.
+    pub fn spawn(&mut self, [x, y]: [f32; 2], [velocity_x, velocity_y]: [f32; 2]) -> Entity {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(x, y, 0.0);
+
+        let velocity = Velocity {
+            x: velocity_x,
+            y: velocity_y,
+        };
+
+        create_ball(
+            &mut self.world,
+            &Scenario::default(),
+            transform,
+            velocity,
+            None,
+        )
+    }
+
+    /// Runs `frames` frames of `delta_seconds` each.
+    pub fn step(&mut self, frames: usize, delta_seconds: f32) {
+        for _ in 0..frames {
+            self.world
+                .write_resource::<Time>()
+                .set_delta_seconds(delta_seconds);
+            self.dispatcher.dispatch(&self.world);
+            self.world.maintain();
+        }
+    }
+
+    /// Where a ball is, or `None` once it has been deleted.
+    pub fn position(&self, ball: Entity) -> Option<[f32; 2]> {
+        if !self.world.is_alive(ball) {
+            return None;
+        }
+
+        let (positions, transforms): (ReadStorage<'_, Position2D>, ReadStorage<'_, Transform>) =
+            self.world.system_data();
+
+        position::translation(positions.get(ball), transforms.get(ball)).map(|[x, y, _]| [x, y])
+    }
+
+    /// How fast a ball is moving, or `None` once it has been deleted.
+    pub fn velocity(&self, ball: Entity) -> Option<[f32; 2]> {
+        if !self.world.is_alive(ball) {
+            return None;
+        }
+
+        self.world
+            .read_storage::<Velocity>()
+            .get(ball)
+            .map(|velocity| [velocity.x, velocity.y])
+    }
+}
+
+/// Asserts two values are within a thousandth of each other.
+pub fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-3,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
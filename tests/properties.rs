@@ -0,0 +1,109 @@
+mod common;
+
+use proptest::{collection::vec, prelude::*};
+
+use bounce::arena::ArenaBounds;
+
+use crate::common::Harness;
+
+/// An arena anywhere around the origin, not just with its corner on it.
+fn arena() -> impl Strategy<Value = ArenaBounds> {
+    (
+        [-1_000.0f32..1_000.0, -1_000.0f32..1_000.0],
+        [1.0f32..2_000.0, 1.0f32..2_000.0],
+    )
+        .prop_map(|([min_x, min_y], [width, height])| ArenaBounds {
+            min_x,
+            min_y,
+            max_x: min_x + width,
+            max_y: min_y + height,
+        })
+}
+
+/// The point a fraction of the way across `arena` along each axis.
+fn at(arena: &ArenaBounds, fraction_x: f32, fraction_y: f32) -> [f32; 2] {
+    [
+        arena.min_x + arena.width() * fraction_x,
+        arena.min_y + arena.height() * fraction_y,
+    ]
+}
+
+/// A ball as a fraction of the way across the arena along each axis, and a velocity.
+fn ball() -> impl Strategy<Value = ([f32; 2], [f32; 2])> {
+    (
+        [0.0f32..1.0, 0.0f32..1.0],
+        [-2_000.0f32..2_000.0, -2_000.0f32..2_000.0],
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn balls_stay_inside_reflecting_walls(
+        arena in arena(),
+        balls in vec(ball(), 1..20),
+        deltas in vec(0.0f32..0.5, 1..30),
+    ) {
+        let mut harness = Harness::with_arena(arena.clone());
+        let balls = balls
+            .into_iter()
+            .map(|([fraction_x, fraction_y], velocity)| {
+                harness.spawn(at(&arena, fraction_x, fraction_y), velocity)
+            })
+            .collect::<Vec<_>>();
+
+        for delta_seconds in deltas {
+            harness.step(1, delta_seconds);
+
+            for ball in &balls {
+                let [x, y] = harness.position(*ball).unwrap();
+
+                prop_assert!(
+                    arena.min_x <= x && x <= arena.max_x,
+                    "x {} outside {}..={}",
+                    x,
+                    arena.min_x,
+                    arena.max_x
+                );
+                prop_assert!(
+                    arena.min_y <= y && y <= arena.max_y,
+                    "y {} outside {}..={}",
+                    y,
+                    arena.min_y,
+                    arena.max_y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn elastic_bounces_keep_the_speed(
+        arena in arena(),
+        balls in vec(ball(), 1..20),
+        deltas in vec(0.0f32..0.5, 1..30),
+    ) {
+        let mut harness = Harness::with_arena(arena.clone());
+        let balls = balls
+            .into_iter()
+            .map(|([fraction_x, fraction_y], [velocity_x, velocity_y])| {
+                let ball = harness.spawn(
+                    at(&arena, fraction_x, fraction_y),
+                    [velocity_x, velocity_y],
+                );
+                (ball, velocity_x.hypot(velocity_y))
+            })
+            .collect::<Vec<_>>();
+
+        for delta_seconds in deltas {
+            harness.step(1, delta_seconds);
+
+            for (ball, speed) in &balls {
+                let [velocity_x, velocity_y] = harness.velocity(*ball).unwrap();
+
+                // A perfect bounce only flips the sign of the velocity across the wall.
+                prop_assert_eq!(velocity_x.hypot(velocity_y), *speed);
+            }
+        }
+    }
+}